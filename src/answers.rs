@@ -0,0 +1,202 @@
+//! The expected answers, keyed by day, part and user, and checking solutions against
+//! them.
+//!
+//! Answers live in `answers.txt`, one per line as `<day> <part> <user> <answer>`, where
+//! the user names whose input the answer is for (see [`input::load_user`]). Blank lines
+//! and lines starting with `#` are ignored.
+
+use std::{path::Path, time::{Duration, Instant}};
+
+use crate::{
+    error::{Error, Result},
+    input,
+    solution::{self, Day, Part},
+};
+
+
+pub const FILE: &str = "answers.txt";
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answer {
+    pub day: u8,
+    pub part: Part,
+    pub user: String,
+    pub expected: String,
+}
+
+
+impl TryFrom<&str> for Answer {
+    type Error = Error;
+
+    fn try_from(line: &str) -> Result<Self> {
+        let mut fields = line.split_whitespace();
+        let mut next = |name: &str| {
+            fields.next().ok_or_else(|| Error::parse(1, 1, format!("missing {name}")))
+        };
+        let day = next("day")?;
+        let day = day.parse::<u8>()
+            .map_err(|_| Error::parse(1, 1, format!("invalid day: {day}")))?;
+        let part = next("part")?.parse::<Part>().map_err(|e| Error::parse(1, 1, e))?;
+        let user = next("user")?.to_owned();
+        let expected = next("answer")?.to_owned();
+        if let Some(extra) = fields.next() {
+            return Err(Error::parse(1, 1, format!("unexpected field: {extra}")))
+        }
+        Ok(Answer { day, part, user, expected })
+    }
+}
+
+
+pub fn parse(input: &str) -> Result<Vec<Answer>> {
+    input.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(i, line)| Answer::try_from(line).map_err(|e| e.on_line(i + 1)))
+        .collect()
+}
+
+
+/// Reads `answers.txt` from the working directory, or else from the crate root.
+pub fn load() -> Result<Vec<Answer>> {
+    let local = Path::new(FILE);
+    let path = match local.is_file() {
+        true => local.to_path_buf(),
+        false => Path::new(env!("CARGO_MANIFEST_DIR")).join(FILE),
+    };
+    let contents = std::fs::read_to_string(&path).map_err(|e| {
+        let message = format!("failed to read {}: {e}", path.display());
+        Error::Io(std::io::Error::new(e.kind(), message))
+    })?;
+    parse(&contents)
+}
+
+
+#[derive(Debug)]
+pub enum Outcome {
+    Pass,
+    Fail { actual: String },
+    Error(Error),
+    /// No answer is recorded for this day and part.
+    Missing,
+}
+
+
+#[derive(Debug)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub user: String,
+    pub outcome: Outcome,
+    /// Parsing plus solving; zero when nothing was run.
+    pub elapsed: Duration,
+}
+
+
+fn check(day: &dyn Day, answer: &Answer) -> Check {
+    let start = Instant::now();
+    let outcome = input::load_user(day.day(), &answer.user)
+        .and_then(|input| day.parse(&input))
+        .and_then(|parsed| day.solve(parsed.as_ref(), answer.part));
+    let elapsed = start.elapsed();
+    let outcome = match outcome {
+        Ok(actual) if actual == answer.expected => Outcome::Pass,
+        Ok(actual) => Outcome::Fail { actual },
+        Err(e) => Outcome::Error(e),
+    };
+    Check { day: day.day(), part: answer.part, user: answer.user.clone(), outcome, elapsed }
+}
+
+
+/// Runs every registered day against the answers recorded for each of `users`, in
+/// day order. A part with no recorded answer shows up as [`Outcome::Missing`] when the
+/// user has an input for the day, and an answer for a day that isn't registered as
+/// [`Outcome::Error`]. Answers for anyone else are skipped.
+pub fn verify(answers: &[Answer], users: &[String]) -> Vec<Check> {
+    let registry = solution::registry();
+    let mut checks = Vec::new();
+    for day in &registry {
+        for part in Part::ALL {
+            for user in users {
+                let mut found = false;
+                for answer in answers.iter()
+                    .filter(|a| a.day == day.day() && a.part == part && &a.user == user)
+                {
+                    found = true;
+                    checks.push(check(day.as_ref(), answer));
+                }
+                let has_input = user == input::DEFAULT_USER
+                    || input::user_path(day.day(), user).is_some();
+                if !found && has_input {
+                    let (outcome, elapsed) = (Outcome::Missing, Duration::ZERO);
+                    let user = user.clone();
+                    checks.push(Check { day: day.day(), part, user, outcome, elapsed });
+                }
+            }
+        }
+    }
+    let unregistered = answers.iter()
+        .filter(|a| users.contains(&a.user) && registry.iter().all(|d| d.day() != a.day));
+    for answer in unregistered {
+        checks.push(Check {
+            day: answer.day,
+            part: answer.part,
+            user: answer.user.clone(),
+            outcome: Outcome::Error(Error::semantic("day is not implemented")),
+            elapsed: Duration::ZERO,
+        });
+    }
+    checks
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Answer, Outcome};
+    use crate::{error::Error, input::DEFAULT_USER, solution::Part};
+
+    #[test]
+    fn parse() {
+        let input = "# comment\n\n7 2 default 14710\n11 one alice cqjxxyzz\n";
+        let output = super::parse(input).unwrap();
+        let answer = |day, part, user: &str, expected: &str| {
+            Answer { day, part, user: user.to_owned(), expected: expected.to_owned() }
+        };
+        let expected = vec![
+            answer(7, Part::Two, "default", "14710"),
+            answer(11, Part::One, "alice", "cqjxxyzz"),
+        ];
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn malformed() {
+        let e = super::parse("1 1 default 232\n1 3 default 1783\n").unwrap_err();
+        assert!(matches!(e, Error::Parse { line: 2, .. }));
+        assert!(super::parse("1 1 default\n").is_err());
+        assert!(super::parse("1 1 default 232 extra\n").is_err());
+    }
+
+    #[test]
+    fn answers_file_covers_every_day() {
+        let answers = super::load().unwrap();
+        for day in crate::solution::registry() {
+            for part in Part::ALL {
+                let recorded = |a: &&Answer| a.day == day.day() && a.part == part;
+                assert!(answers.iter().filter(recorded).any(|a| a.user == DEFAULT_USER));
+            }
+        }
+    }
+
+    #[test]
+    fn verify() {
+        let input = "1 1 default 232\n1 2 default 0\n26 1 default 1\n3 1 alice 1\n";
+        let answers = super::parse(input).unwrap();
+        let output = super::verify(&answers, &[String::from("default")]);
+        assert!(matches!(output[0].outcome, Outcome::Pass));
+        assert!(matches!(&output[1].outcome, Outcome::Fail { actual } if actual == "1783"));
+        assert!(matches!(output[2].outcome, Outcome::Missing));
+        assert!(matches!(output.last().unwrap().outcome, Outcome::Error(_)));
+        assert!(output.iter().all(|c| c.user == "default"));
+    }
+}
@@ -0,0 +1,317 @@
+//! Timing parsing and each part of every day.
+//!
+//! Each phase is run `warmup` times untimed and then `samples` times timed. Results are
+//! summarised per phase and can be written out as JSON, then read back as a baseline
+//! that later runs are compared against:
+//!
+//! ```json
+//! {"days": [{"day": 1, "title": "...", "parse": {"median_ns": 1200, ...}, ...}]}
+//! ```
+
+use std::{fmt::Display, time::{Duration, Instant}};
+
+use serde_json::{json, Value};
+
+use crate::{error::{Error, Result}, solution::{Day, Part}};
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Options {
+    pub warmup: usize,
+    pub samples: usize,
+}
+
+
+impl Default for Options {
+    fn default() -> Self {
+        Options { warmup: 1, samples: 10 }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Parse,
+    PartOne,
+    PartTwo,
+}
+
+
+impl Phase {
+    pub const ALL: [Phase; 3] = [Phase::Parse, Phase::PartOne, Phase::PartTwo];
+
+    fn key(&self) -> &'static str {
+        match self {
+            Phase::Parse => "parse",
+            Phase::PartOne => "part_one",
+            Phase::PartTwo => "part_two",
+        }
+    }
+}
+
+
+impl Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Phase::Parse => f.pad("parse"),
+            Phase::PartOne => f.pad("part 1"),
+            Phase::PartTwo => f.pad("part 2"),
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+
+impl Stats {
+    /// Summarises `samples`, which must not be empty. Percentiles use the nearest rank.
+    pub fn from_samples(samples: &mut [Duration]) -> Self {
+        samples.sort();
+        let percentile = |p: usize| {
+            let rank = (p * samples.len()).div_ceil(100).max(1);
+            samples[rank - 1]
+        };
+        Stats {
+            min: samples[0],
+            median: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: samples[samples.len() - 1],
+        }
+    }
+
+    fn to_json(self) -> Value {
+        json!({
+            "min_ns": self.min.as_nanos() as u64,
+            "median_ns": self.median.as_nanos() as u64,
+            "p90_ns": self.p90.as_nanos() as u64,
+            "p99_ns": self.p99.as_nanos() as u64,
+            "max_ns": self.max.as_nanos() as u64,
+        })
+    }
+
+    fn from_json(value: &Value) -> Result<Self> {
+        let field = |name: &str| {
+            value.get(name)
+                .and_then(Value::as_u64)
+                .map(Duration::from_nanos)
+                .ok_or_else(|| Error::semantic(format!("benchmark stats lack `{name}`")))
+        };
+        Ok(Stats {
+            min: field("min_ns")?,
+            median: field("median_ns")?,
+            p90: field("p90_ns")?,
+            p99: field("p99_ns")?,
+            max: field("max_ns")?,
+        })
+    }
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DayResult {
+    pub day: u8,
+    pub title: String,
+    pub parse: Stats,
+    pub part_one: Stats,
+    pub part_two: Stats,
+}
+
+
+impl DayResult {
+    pub fn phase(&self, phase: Phase) -> Stats {
+        match phase {
+            Phase::Parse => self.parse,
+            Phase::PartOne => self.part_one,
+            Phase::PartTwo => self.part_two,
+        }
+    }
+}
+
+
+fn time<T>(options: Options, mut f: impl FnMut() -> Result<T>) -> Result<Stats> {
+    for _ in 0..options.warmup {
+        f()?;
+    }
+    let mut samples = Vec::with_capacity(options.samples);
+    for _ in 0..options.samples {
+        let start = Instant::now();
+        f()?;
+        samples.push(start.elapsed());
+    }
+    Ok(Stats::from_samples(&mut samples))
+}
+
+
+pub fn run_day(day: &dyn Day, input: &str, options: Options) -> Result<DayResult> {
+    if options.samples == 0 {
+        return Err(Error::semantic("at least one sample is required"))
+    }
+    let parse = time(options, || day.parse(input))?;
+    let parsed = day.parse(input)?;
+    let part_one = time(options, || day.solve(parsed.as_ref(), Part::One))?;
+    let part_two = time(options, || day.solve(parsed.as_ref(), Part::Two))?;
+    let title = day.title().to_owned();
+    Ok(DayResult { day: day.day(), title, parse, part_one, part_two })
+}
+
+
+pub fn to_json(results: &[DayResult]) -> Value {
+    let days: Vec<Value> = results.iter()
+        .map(|r| {
+            let mut day = json!({ "day": r.day, "title": r.title });
+            for phase in Phase::ALL {
+                day[phase.key()] = r.phase(phase).to_json();
+            }
+            day
+        })
+        .collect();
+    json!({ "days": days })
+}
+
+
+pub fn from_json(value: &Value) -> Result<Vec<DayResult>> {
+    let days = value.get("days")
+        .and_then(Value::as_array)
+        .ok_or_else(|| Error::semantic("benchmark results lack a `days` array"))?;
+    days.iter()
+        .map(|day| {
+            let number = day.get("day")
+                .and_then(Value::as_u64)
+                .and_then(|d| u8::try_from(d).ok())
+                .ok_or_else(|| Error::semantic("benchmark result lacks a valid `day`"))?;
+            let phase = |phase: Phase| {
+                let stats = day.get(phase.key()).ok_or_else(|| Error::semantic(format!(
+                    "benchmark result for day {number} lacks `{}`", phase.key()
+                )))?;
+                Stats::from_json(stats)
+            };
+            let title = day.get("title").and_then(Value::as_str).unwrap_or_default();
+            Ok(DayResult {
+                day: number,
+                title: title.to_owned(),
+                parse: phase(Phase::Parse)?,
+                part_one: phase(Phase::PartOne)?,
+                part_two: phase(Phase::PartTwo)?,
+            })
+        })
+        .collect()
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Regression {
+    pub day: u8,
+    pub phase: Phase,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+
+impl Display for Regression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ratio = self.current.as_secs_f64() / self.baseline.as_secs_f64();
+        let change = ratio * 100.0 - 100.0;
+        write!(
+            f,
+            "day {} {}: median {:?} -> {:?} (+{change:.0}%)",
+            self.day, self.phase, self.baseline, self.current,
+        )
+    }
+}
+
+
+/// Every phase whose median is more than `threshold` percent slower than in `baseline`.
+/// Days missing from the baseline are not compared.
+pub fn compare(
+    current: &[DayResult],
+    baseline: &[DayResult],
+    threshold: u32,
+) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+    for result in current {
+        let Some(base) = baseline.iter().find(|b| b.day == result.day) else {
+            continue
+        };
+        for phase in Phase::ALL {
+            let (before, after) = (base.phase(phase).median, result.phase(phase).median);
+            if after.as_nanos() * 100 > before.as_nanos() * (100 + threshold as u128) {
+                regressions.push(Regression {
+                    day: result.day,
+                    phase,
+                    baseline: before,
+                    current: after,
+                });
+            }
+        }
+    }
+    regressions
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{DayResult, Options, Phase, Stats};
+
+    fn stats(median_ms: u64) -> Stats {
+        let median = Duration::from_millis(median_ms);
+        Stats { min: median, median, p90: median, p99: median, max: median }
+    }
+
+    fn result(day: u8, part_one_ms: u64) -> DayResult {
+        DayResult {
+            day,
+            title: String::from("test"),
+            parse: stats(1),
+            part_one: stats(part_one_ms),
+            part_two: stats(5),
+        }
+    }
+
+    #[test]
+    fn percentiles() {
+        let mut samples: Vec<Duration> =
+            (1..=10).rev().map(Duration::from_millis).collect();
+        let output = Stats::from_samples(&mut samples);
+        assert_eq!(output.min, Duration::from_millis(1));
+        assert_eq!(output.median, Duration::from_millis(5));
+        assert_eq!(output.p90, Duration::from_millis(9));
+        assert_eq!(output.p99, Duration::from_millis(10));
+        assert_eq!(output.max, Duration::from_millis(10));
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let results = vec![result(1, 2), result(3, 4)];
+        let output = super::from_json(&super::to_json(&results)).unwrap();
+        assert_eq!(output, results);
+    }
+
+    #[test]
+    fn compare() {
+        let baseline = vec![result(1, 10), result(2, 10)];
+        let current = vec![result(1, 11), result(2, 12), result(3, 100)];
+        let output = super::compare(&current, &baseline, 10);
+        assert_eq!(output.len(), 1);
+        assert_eq!((output[0].day, output[0].phase), (2, Phase::PartOne));
+    }
+
+    #[test]
+    fn run_day() {
+        let day = crate::solution::get(1).unwrap();
+        let options = Options { warmup: 0, samples: 3 };
+        let output = super::run_day(day.as_ref(), "())", options).unwrap();
+        assert_eq!(output.day, 1);
+        assert!(output.parse.min <= output.parse.max);
+    }
+}
@@ -0,0 +1,292 @@
+//! Command-line argument parsing for the runner binary.
+
+use std::{path::PathBuf, str::FromStr};
+
+use aoc_2015_rs::{input::Source, solution::Part};
+
+
+pub const USAGE: &str = "\
+usage:
+  aoc-2015-rs list
+  aoc-2015-rs run <day> [part] [input] [users]
+  aoc-2015-rs all [users]
+  aoc-2015-rs verify [users]
+  aoc-2015-rs bench [day] [options]
+
+  <day>     day number, 1-12
+  [part]    1, 2 or `both` (default: both)
+  [input]   path to an input file, `-` to read stdin, or one of
+              @env       dayNN.txt in $AOC_INPUT_DIR
+              @inputs    inputs/dayNN.txt
+              @embedded  the copy built in with `--features embed-inputs`
+              @<user>    inputs/<user>/dayNN.txt
+            (default: the first of those that exists)
+  [users]   --user <user> for one user's inputs, or --all-users for everyone's
+            (default: your own, except for `verify`, which checks everyone's)
+
+bench options (build with `--release` for meaningful numbers):
+  --warmup <n>       untimed runs before sampling (default: 1)
+  --samples <n>      timed runs per phase (default: 10)
+  --out <file>       write the results as JSON
+  --baseline <file>  compare against earlier results, failing on regressions
+  --threshold <pct>  slowdown of the median that counts as a regression (default: 10)
+  --scaling <n>      instead, time day 4's miner on 1, 2, 4, ... up to n threads
+  --sample-ms <ms>   how long to time each thread count for with --scaling
+                     (default: 1000)";
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    List,
+    All(Users),
+    /// Check every day against `answers.txt`.
+    Verify(Users),
+    Run {
+        day: u8,
+        part: Option<Part>,
+        input: Option<Source>,
+        users: Users,
+    },
+    Bench(Bench),
+}
+
+
+/// Whose inputs to run on; see [`aoc_2015_rs::input::users`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Users {
+    #[default]
+    Default,
+    One(String),
+    All,
+}
+
+
+/// Pulls `--user <name>` or `--all-users` out of `args`.
+fn take_users(args: &mut Vec<&str>) -> Result<Users, String> {
+    let mut users = Users::Default;
+    while let Some(i) = args.iter().position(|a| *a == "--user" || *a == "--all-users") {
+        if users != Users::Default {
+            return Err(String::from("only one of --user and --all-users may be given"))
+        }
+        users = match args.remove(i) {
+            "--all-users" => Users::All,
+            _ if i < args.len() => Users::One(args.remove(i).to_owned()),
+            _ => return Err(String::from("missing value for --user")),
+        };
+    }
+    Ok(users)
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Bench {
+    pub day: Option<u8>,
+    pub warmup: usize,
+    pub samples: usize,
+    pub output: Option<PathBuf>,
+    pub baseline: Option<PathBuf>,
+    /// Percent.
+    pub threshold: u32,
+    /// Time the day 4 miner on up to this many threads instead.
+    pub scaling: Option<usize>,
+    /// How long to time each thread count for when scaling.
+    pub sample_ms: u64,
+}
+
+
+impl Default for Bench {
+    fn default() -> Self {
+        Bench {
+            day: None,
+            warmup: 1,
+            samples: 10,
+            output: None,
+            baseline: None,
+            threshold: 10,
+            scaling: None,
+            sample_ms: 1000,
+        }
+    }
+}
+
+
+impl TryFrom<&[&str]> for Bench {
+    type Error = String;
+
+    /// `args` are those after `bench`.
+    fn try_from(args: &[&str]) -> Result<Self, Self::Error> {
+        let mut bench = Bench::default();
+        let mut args = args.iter();
+        while let Some(&arg) = args.next() {
+            if !arg.starts_with("--") {
+                match bench.day {
+                    None => bench.day = Some(parse_day(arg)?),
+                    Some(_) => return Err(format!("unexpected argument: {arg}")),
+                }
+                continue
+            }
+            let value = *args.next().ok_or_else(|| format!("missing value for {arg}"))?;
+            match arg {
+                "--warmup" => bench.warmup = parse_number(arg, value)?,
+                "--samples" => bench.samples = parse_number(arg, value)?,
+                "--out" => bench.output = Some(PathBuf::from(value)),
+                "--baseline" => bench.baseline = Some(PathBuf::from(value)),
+                "--threshold" => bench.threshold = parse_number(arg, value)?,
+                "--scaling" => bench.scaling = Some(parse_number(arg, value)?),
+                "--sample-ms" => bench.sample_ms = parse_number(arg, value)?,
+                _ => return Err(format!("unknown option: {arg}")),
+            }
+        }
+        let saves = bench.output.is_some() || bench.baseline.is_some();
+        if bench.scaling.is_some() && saves {
+            return Err(String::from("--scaling takes no --out or --baseline"))
+        }
+        Ok(bench)
+    }
+}
+
+
+/// `value` as the type of the option `arg` sets, rejecting anything out of its range.
+fn parse_number<T: FromStr>(arg: &str, value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid value for {arg}: {value}"))
+}
+
+
+fn parse_day(s: &str) -> Result<u8, String> {
+    s.parse::<u8>().map_err(|_| format!("invalid day: {s}"))
+}
+
+
+/// A part is only recognised in the second position; anything else there is taken to
+/// be the input, so `run 3 input.txt` works as well as `run 3 both input.txt`.
+fn parse_part(s: &str) -> Option<Option<Part>> {
+    match s {
+        "both" => Some(None),
+        _ => s.parse::<Part>().ok().map(Some),
+    }
+}
+
+
+impl TryFrom<&[String]> for Command {
+    type Error = String;
+
+    /// `args` should not include the program name.
+    fn try_from(args: &[String]) -> Result<Self, Self::Error> {
+        let mut args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+        if args.first() == Some(&"bench") {
+            return Bench::try_from(&args[1..]).map(Command::Bench)
+        }
+        let users = take_users(&mut args)?;
+        match args.as_slice() {
+            [] | ["list"] if users == Users::Default => Ok(Command::List),
+            ["all"] => Ok(Command::All(users)),
+            ["verify"] => Ok(Command::Verify(users)),
+            ["run", day, rest @ ..] => {
+                let day = parse_day(day)?;
+                let (part, rest) = match rest.split_first() {
+                    Some((first, tail)) => match parse_part(first) {
+                        Some(part) => (part, tail),
+                        None => (None, rest),
+                    },
+                    None => (None, rest),
+                };
+                let input = match rest {
+                    [] => None,
+                    [_] if users != Users::Default => {
+                        return Err(String::from("an input can't be combined with users"))
+                    },
+                    [input] => Some(Source::from(*input)),
+                    _ => return Err(format!("unexpected arguments: {}", rest.join(" "))),
+                };
+                Ok(Command::Run { day, part, input, users })
+            },
+            ["run"] => Err(String::from("missing day")),
+            [] | ["list"] => Err(String::from("list takes no options")),
+            [other, ..] => Err(format!("unknown command: {other}")),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{Bench, Command, Users};
+    use aoc_2015_rs::{input::Source, solution::Part};
+
+    fn parse(args: &[&str]) -> Result<Command, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        Command::try_from(args.as_slice())
+    }
+
+    #[test]
+    fn run() {
+        let cases = vec![
+            (vec!["run", "1"], 1, None, None),
+            (vec!["run", "7", "2"], 7, Some(Part::Two), None),
+            (vec!["run", "7", "both", "-"], 7, None, Some(Source::Stdin)),
+            (
+                vec!["run", "12", "in.json"],
+                12,
+                None,
+                Some(Source::Path(PathBuf::from("in.json"))),
+            ),
+            (
+                vec!["run", "3", "one", "in.txt"],
+                3,
+                Some(Part::One),
+                Some(Source::Path(PathBuf::from("in.txt"))),
+            ),
+        ];
+        for (args, day, part, input) in cases {
+            let output = parse(&args).unwrap();
+            assert_eq!(output, Command::Run { day, part, input, users: Users::Default });
+        }
+    }
+
+    #[test]
+    fn users() {
+        let output = parse(&["run", "--user", "alice", "3", "2"]).unwrap();
+        let users = Users::One(String::from("alice"));
+        let expected = Command::Run { day: 3, part: Some(Part::Two), input: None, users };
+        assert_eq!(output, expected);
+        assert_eq!(parse(&["all", "--all-users"]).unwrap(), Command::All(Users::All));
+        assert_eq!(parse(&["verify"]).unwrap(), Command::Verify(Users::Default));
+        assert!(parse(&["all", "--user"]).is_err());
+        assert!(parse(&["all", "--user", "a", "--all-users"]).is_err());
+        assert!(parse(&["run", "1", "in.txt", "--all-users"]).is_err());
+    }
+
+    #[test]
+    fn bench() {
+        assert_eq!(parse(&["bench"]).unwrap(), Command::Bench(Bench::default()));
+        let args = ["bench", "4", "--samples", "3", "--baseline", "b.json"];
+        let output = parse(&args).unwrap();
+        let expected = Bench {
+            day: Some(4),
+            samples: 3,
+            baseline: Some(PathBuf::from("b.json")),
+            ..Bench::default()
+        };
+        assert_eq!(output, Command::Bench(expected));
+        let output = parse(&["bench", "--scaling", "64", "--sample-ms", "250"]).unwrap();
+        let expected = Bench { scaling: Some(64), sample_ms: 250, ..Bench::default() };
+        assert_eq!(output, Command::Bench(expected));
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(parse(&["run"]).is_err());
+        assert!(parse(&["run", "x"]).is_err());
+        assert!(parse(&["run", "1", "1", "a", "b"]).is_err());
+        assert!(parse(&["frobnicate"]).is_err());
+        assert!(parse(&["bench", "--samples"]).is_err());
+        assert!(parse(&["bench", "--samples", "x"]).is_err());
+        assert!(parse(&["bench", "--threshold", "4294967306"]).is_err());
+        assert!(parse(&["bench", "--sample-ms", "-1"]).is_err());
+        assert!(parse(&["bench", "1", "2"]).is_err());
+        assert!(parse(&["bench", "--scaling", "4", "--out", "o.json"]).is_err());
+        assert!(parse(&["bench", "--baseline", "b.json", "--scaling", "4"]).is_err());
+    }
+}
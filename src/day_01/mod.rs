@@ -35,63 +35,516 @@
 //! ()()) causes him to enter the basement at character position 5.
 //! What is the position of the character that causes Santa to first enter the basement?
 
-use std::{fs::File, path::Path};
-
-fn part_one(input: &str) -> i32 {
-    let mut floor: i32 = 0;
-    for c in input.chars() {
-        match c {
-            '(' => floor += 1,
-            ')' => floor -= 1,
-            '\n' => {},
-            _ => unreachable!("unexpected character from input")
+use std::io::BufRead;
+
+use rayon::prelude::*;
+
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+    stream::Bytes,
+    tokens::{Mode, Skipped, TokenMap},
+};
+
+/// Line endings (including the `\r` of CRLF files) are skipped; anything else that
+/// isn't a parenthesis is an error.
+fn delta<R: BufRead>(bytes: &Bytes<R>, byte: u8) -> Result<i64> {
+    match byte {
+        b'(' => Ok(1),
+        b')' => Ok(-1),
+        b'\n' | b'\r' => Ok(0),
+        _ => Err(bytes.unexpected(byte)),
+    }
+}
+
+
+/// [`part_one`], reading the instructions from `reader` as they come. The floor is
+/// kept in an `i64`, which no input that fits on a disk can overflow.
+pub fn part_one_reader(reader: impl BufRead) -> Result<i64> {
+    let mut bytes = Bytes::new(reader);
+    let mut floor: i64 = 0;
+    while let Some(byte) = bytes.next() {
+        floor += delta(&bytes, byte?)?;
+    }
+    Ok(floor)
+}
+
+
+/// [`part_two`], reading the instructions from `reader` only until Santa enters the
+/// basement.
+pub fn part_two_reader(reader: impl BufRead) -> Result<Option<usize>> {
+    let mut bytes = Bytes::new(reader);
+    let mut floor: i64 = 0;
+    let mut position = 0;
+    while let Some(byte) = bytes.next() {
+        position += 1;
+        floor += delta(&bytes, byte?)?;
+        if floor < 0 {
+            return Ok(Some(position))
+        }
+    }
+    Ok(None)
+}
+
+
+/// The floor the instructions end on.
+pub fn part_one(input: &str) -> Result<i64> {
+    part_one_reader(input.as_bytes())
+}
+
+
+/// The 1-based position of the instruction that first takes Santa below floor 0.
+pub fn part_two(input: &str) -> Result<Option<usize>> {
+    part_two_reader(input.as_bytes())
+}
+
+
+/// `(` up a floor and `)` down one.
+pub fn default_tokens() -> TokenMap<i32> {
+    TokenMap::new([("(", 1), (")", -1)]).expect("the default tokens are distinct")
+}
+
+
+/// [`part_one`] with the floor changes spelled by `tokens`, along with any characters
+/// skipped in lenient mode.
+pub fn part_one_with(
+    input: &str,
+    tokens: &TokenMap<i32>,
+    mode: Mode,
+) -> Result<(i64, Skipped)> {
+    let mut scan = tokens.scan(input, mode);
+    let mut floor: i64 = 0;
+    for token in scan.by_ref() {
+        floor += i64::from(*token?.value);
+    }
+    Ok((floor, scan.into_skipped()))
+}
+
+
+/// [`part_two`] with the floor changes spelled by `tokens`. The position is of the first
+/// character of the token that takes Santa into the basement, counting every character.
+pub fn part_two_with(
+    input: &str,
+    tokens: &TokenMap<i32>,
+    mode: Mode,
+) -> Result<(Option<usize>, Skipped)> {
+    let mut scan = tokens.scan(input, mode);
+    let mut floor: i64 = 0;
+    for token in scan.by_ref() {
+        let token = token?;
+        floor += i64::from(*token.value);
+        if floor < 0 {
+            return Ok((Some(token.position), scan.into_skipped()))
+        }
+    }
+    Ok((None, scan.into_skipped()))
+}
+
+
+/// Every floor Santa passes through, built once from the instructions so questions
+/// about the whole trip are cheap to answer.
+///
+/// Steps are numbered like [`part_two`]'s positions: step 0 is the start on floor 0
+/// and step `n` is the floor after the `n`th character. Line endings count as steps
+/// that stay on the same floor.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FloorTrace {
+    floors: Vec<i64>,
+    min: i64,
+    max: i64,
+    /// The step each floor from `min` to `max` is first reached at; floors only ever
+    /// change by one, so every floor in between is reached.
+    first: Vec<usize>,
+    basement_entries: usize,
+}
+
+
+impl FloorTrace {
+    /// The number of steps, not counting the start.
+    pub fn len(&self) -> usize {
+        self.floors.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The floor after `step` steps, or `None` past the end.
+    pub fn floor_at(&self, step: usize) -> Option<i64> {
+        self.floors.get(step).copied()
+    }
+
+    pub fn final_floor(&self) -> i64 {
+        self.floors[self.len()]
+    }
+
+    pub fn min_floor(&self) -> i64 {
+        self.min
+    }
+
+    pub fn max_floor(&self) -> i64 {
+        self.max
+    }
+
+    /// The step `floor` is first reached at, if it ever is.
+    pub fn first_reached(&self, floor: i64) -> Option<usize> {
+        if floor < self.min || floor > self.max {
+            return None
         }
+        Some(self.first[(floor - self.min) as usize])
+    }
+
+    /// Every floor reached, from lowest to highest, with the step it's first reached at.
+    pub fn first_reached_all(&self) -> impl Iterator<Item = (i64, usize)> + '_ {
+        (self.min..=self.max).zip(self.first.iter().copied())
+    }
+
+    /// How many times Santa steps from the ground floor down into the basement.
+    pub fn basement_entries(&self) -> usize {
+        self.basement_entries
     }
-    floor
 }
 
 
-fn part_two(input: &str) -> Option<usize> {
-    let mut floor: i32 = 0;
-    for (i, c) in input.chars().enumerate() {
-        match c {
-            '(' => floor += 1,
-            ')' => {
-                floor -= 1;
-                if floor < 0 {
-                    return Some(i+1)
-                }
+impl TryFrom<&str> for FloorTrace {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Self> {
+        let mut bytes = Bytes::new(input.as_bytes());
+        let mut floors = vec![0];
+        let (mut min, mut max) = (0, 0);
+        let mut first_below = Vec::new();
+        let mut first_above = vec![0];
+        let mut basement_entries = 0;
+        while let Some(byte) = bytes.next() {
+            let floor = floors[floors.len() - 1] + delta(&bytes, byte?)?;
+            let step = floors.len();
+            if floor < min {
+                min = floor;
+                first_below.push(step);
+            } else if floor > max {
+                max = floor;
+                first_above.push(step);
             }
-            '\n' => {},
-            _ => unreachable!("unexpected character from input")
+            if floor == -1 && floors[step - 1] == 0 {
+                basement_entries += 1;
+            }
+            floors.push(floor);
         }
+        let first = first_below.into_iter().rev().chain(first_above).collect();
+        Ok(FloorTrace { floors, min, max, first, basement_entries })
     }
-    None
 }
 
 
-fn load_input() -> std::io::Result<String> {
-    let mod_path = Path::new(file!())
-        .parent()
-        .unwrap()
-        .join("input.txt");
-    std::fs::read_to_string(mod_path)
+/// The instructions read as a bracket sequence. Positions are numbered like
+/// [`part_two`]'s, from 1, and line endings are skipped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Brackets {
+    /// Matching `(` and `)` positions, ordered by the `)`.
+    pub pairs: Vec<(usize, usize)>,
+    /// Positions of `(` that are never closed.
+    pub unmatched_open: Vec<usize>,
+    /// Positions of `)` with nothing open to close.
+    pub unmatched_close: Vec<usize>,
+    /// The most brackets open at once, counting matched pairs only.
+    pub max_depth: usize,
+    /// Positions of the `)` that, flipped to `(`, keep Santa out of the basement with
+    /// as few flips as possible.
+    pub basement_flips: Vec<usize>,
+    n_brackets: usize,
+    floor: i64,
+}
+
+
+impl Brackets {
+    /// The fewest flips of `(` to `)` or back that end the trip on floor `target`, or
+    /// `None` if no number of flips can. Each flip moves the final floor by two.
+    pub fn flips_to_floor(&self, target: i64) -> Option<usize> {
+        let distance = self.floor.abs_diff(target) as usize;
+        let reachable = target.unsigned_abs() as usize <= self.n_brackets;
+        match distance.is_multiple_of(2) && reachable {
+            true => Some(distance / 2),
+            false => None,
+        }
+    }
+
+    /// The fewest flips that keep Santa from ever entering the basement.
+    pub fn flips_to_avoid_basement(&self) -> usize {
+        self.basement_flips.len()
+    }
+}
+
+
+impl TryFrom<&str> for Brackets {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Self> {
+        let mut bytes = Bytes::new(input.as_bytes());
+        let mut open = Vec::new();
+        let (mut pairs, mut unmatched_close, mut basement_flips) = (vec![], vec![], vec![]);
+        let (mut n_brackets, mut floor, mut flipped_floor) = (0, 0, 0);
+        let mut position = 0;
+        while let Some(byte) = bytes.next() {
+            position += 1;
+            let delta = delta(&bytes, byte?)?;
+            if delta == 0 {
+                continue
+            }
+            n_brackets += 1;
+            floor += delta;
+            flipped_floor += delta;
+            if flipped_floor < 0 {
+                flipped_floor += 2;
+                basement_flips.push(position);
+            }
+            match delta {
+                1 => open.push(position),
+                _ => match open.pop() {
+                    Some(start) => pairs.push((start, position)),
+                    None => unmatched_close.push(position),
+                },
+            }
+        }
+        // Matched pairs nest or follow one another, so walking their ends in order
+        // gives the depth at each point.
+        let mut ends: Vec<(usize, isize)> = pairs.iter()
+            .flat_map(|&(start, end)| [(start, 1), (end, -1)])
+            .collect();
+        ends.sort_unstable();
+        let depths = ends.iter().scan(0, |depth, &(_, step)| {
+            *depth += step;
+            Some(*depth as usize)
+        });
+        let max_depth = depths.max().unwrap_or(0);
+        Ok(Brackets {
+            pairs,
+            unmatched_open: open,
+            unmatched_close,
+            max_depth,
+            basement_flips,
+            n_brackets,
+            floor,
+        })
+    }
+}
+
+
+pub const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+
+/// What a chunk of the instructions does on its own, up to its first stray character.
+#[derive(Clone, Copy, Debug)]
+struct Chunk {
+    net: i64,
+    /// The lowest floor reached relative to the start of the chunk.
+    min: i64,
+    /// The offset of the first stray character in the chunk.
+    error: Option<usize>,
+}
+
+
+fn summarise(chunk: &[u8]) -> Chunk {
+    let (mut net, mut min) = (0, 0);
+    for (i, byte) in chunk.iter().enumerate() {
+        match byte {
+            b'(' => net += 1,
+            b')' => net -= 1,
+            b'\n' | b'\r' => {},
+            _ => return Chunk { net, min, error: Some(i) },
+        }
+        min = min.min(net);
+    }
+    Chunk { net, min, error: None }
+}
+
+
+fn summarise_par(input: &[u8], chunk_size: usize) -> Result<Vec<Chunk>> {
+    if chunk_size == 0 {
+        return Err(Error::semantic("chunks must hold at least one instruction"))
+    }
+    Ok(input.par_chunks(chunk_size).map(summarise).collect())
+}
+
+
+/// [`part_one`] for huge inputs: each chunk of `chunk_size` bytes is summed on the
+/// rayon pool and the sums combined in order, in an `i64` so that no input that fits
+/// in memory can overflow it. Errors are the same as `part_one`'s.
+pub fn part_one_par(input: &[u8], chunk_size: usize) -> Result<i64> {
+    let mut floor = 0;
+    for (k, chunk) in summarise_par(input, chunk_size)?.into_iter().enumerate() {
+        if let Some(i) = chunk.error {
+            return part_one_reader(&input[..=k * chunk_size + i])
+        }
+        floor += chunk.net;
+    }
+    Ok(floor)
+}
+
+
+/// [`part_two`] for huge inputs: each chunk's net move and lowest relative floor are
+/// worked out on the rayon pool, which is enough to tell which chunk Santa first enters
+/// the basement in; only that chunk is then walked step by step.
+pub fn part_two_par(input: &[u8], chunk_size: usize) -> Result<Option<usize>> {
+    let mut floor = 0;
+    for (k, chunk) in summarise_par(input, chunk_size)?.into_iter().enumerate() {
+        let start = k * chunk_size;
+        if floor + chunk.min < 0 {
+            let end = (start + chunk_size).min(input.len());
+            let position = input[start..end].iter()
+                .scan(floor, |floor, byte| {
+                    *floor += match byte { b'(' => 1, b')' => -1, _ => 0 };
+                    Some(*floor)
+                })
+                .position(|floor| floor < 0);
+            return Ok(position.map(|i| start + i + 1))
+        }
+        if let Some(i) = chunk.error {
+            return part_two_reader(&input[..=start + i])
+        }
+        floor += chunk.net;
+    }
+    Ok(None)
+}
+
+
+pub struct Day01;
+
+
+impl Solution for Day01 {
+    const DAY: u8 = 1;
+    const TITLE: &'static str = "Not Quite Lisp";
+
+    type Input = String;
+    type Output1 = i64;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.to_owned())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        part_one(input)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        part_two(input)?.ok_or_else(|| Error::semantic("Santa never enters the basement"))
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use crate::input::load_day;
+
     #[test]
     fn part_one() {
-        let input = super::load_input().unwrap();
-        let output = super::part_one(&input);
+        let input = load_day(1).unwrap();
+        let output = super::part_one(&input).unwrap();
         assert_eq!(output, 232);
     }
 
     #[test]
     fn part_two() {
-        let input = super::load_input().unwrap();
-        let output = super::part_two(&input);
+        let input = load_day(1).unwrap();
+        let output = super::part_two(&input).unwrap();
         assert_eq!(output, Some(1783));
     }
+
+    #[test]
+    fn stray_character() {
+        assert_eq!(super::part_one("(()\r\n").unwrap(), 1);
+        let e = super::part_one("((\n(x").unwrap_err();
+        let expected = "parse error at line 2, column 2: unexpected character 'x'";
+        assert_eq!(e.to_string(), expected);
+    }
+
+    #[test]
+    fn trace() {
+        let trace = super::FloorTrace::try_from("(()))\n)((").unwrap();
+        assert_eq!(trace.len(), 9);
+        assert_eq!(trace.final_floor(), 0);
+        assert_eq!(trace.floor_at(5), Some(-1));
+        assert_eq!(trace.floor_at(10), None);
+        assert_eq!((trace.min_floor(), trace.max_floor()), (-2, 2));
+        assert_eq!(trace.first_reached(-1), Some(5));
+        assert_eq!(trace.first_reached(3), None);
+        let expected = vec![(-2, 7), (-1, 5), (0, 0), (1, 1), (2, 2)];
+        assert_eq!(trace.first_reached_all().collect::<Vec<_>>(), expected);
+        assert_eq!(trace.basement_entries(), 1);
+    }
+
+    #[test]
+    fn trace_matches_parts() {
+        let input = load_day(1).unwrap();
+        let trace = super::FloorTrace::try_from(input.as_str()).unwrap();
+        assert_eq!(trace.final_floor(), 232);
+        assert_eq!(trace.first_reached(-1), Some(1783));
+    }
+
+    #[test]
+    fn tokens() {
+        use crate::tokens::{Mode, TokenMap};
+
+        let input = load_day(1).unwrap();
+        let tokens = super::default_tokens();
+        let (output, _) = super::part_one_with(&input, &tokens, Mode::Strict).unwrap();
+        assert_eq!(output, 232);
+        let tokens = TokenMap::new([("up", 1), ("down", -1)]).unwrap();
+        let (output, skipped) = super::part_two_with("up,down,down", &tokens, Mode::Lenient)
+            .unwrap();
+        assert_eq!((output, skipped[&',']), (Some(9), 2));
+        assert!(super::part_two_with("up,down,down", &tokens, Mode::Strict).is_err());
+        let tokens = TokenMap::new([("^", i32::MAX)]).unwrap();
+        let (output, _) = super::part_one_with("^^^", &tokens, Mode::Strict).unwrap();
+        assert_eq!(output, 3 * i64::from(i32::MAX));
+    }
+
+    #[test]
+    fn brackets() {
+        let brackets = super::Brackets::try_from("(()))\n(()(").unwrap();
+        assert_eq!(brackets.pairs, vec![(2, 3), (1, 4), (8, 9)]);
+        assert_eq!(brackets.unmatched_open, vec![7, 10]);
+        assert_eq!(brackets.unmatched_close, vec![5]);
+        assert_eq!(brackets.max_depth, 2);
+        assert_eq!(brackets.basement_flips, vec![5]);
+        assert_eq!(brackets.flips_to_floor(1), Some(0));
+        assert_eq!(brackets.flips_to_floor(-3), Some(2));
+        assert_eq!(brackets.flips_to_floor(2), None);
+        assert_eq!(brackets.flips_to_floor(11), None);
+        let brackets = super::Brackets::try_from(")))(((").unwrap();
+        assert_eq!(brackets.flips_to_avoid_basement(), 2);
+        let brackets = super::Brackets::try_from("((()(").unwrap();
+        assert_eq!((brackets.max_depth, brackets.unmatched_open.len()), (1, 3));
+        assert_eq!(super::Brackets::try_from("(((").unwrap().max_depth, 0);
+    }
+
+    #[test]
+    fn par() {
+        let input = load_day(1).unwrap();
+        for chunk_size in [1, 7, 4096, input.len() + 1] {
+            let output = super::part_one_par(input.as_bytes(), chunk_size).unwrap();
+            assert_eq!(output, 232);
+            let output = super::part_two_par(input.as_bytes(), chunk_size).unwrap();
+            assert_eq!(output, Some(1783));
+        }
+        for input in ["((\n(x", "(()x))", "())x", "((("] {
+            let expected = (super::part_one(input), super::part_two(input));
+            let bytes = input.as_bytes();
+            let output = (super::part_one_par(bytes, 2), super::part_two_par(bytes, 2));
+            assert_eq!(format!("{output:?}"), format!("{expected:?}"));
+        }
+        assert!(super::part_one_par(b"()", 0).is_err());
+    }
+
+    #[test]
+    fn reader() {
+        use std::io::Read;
+
+        let input = std::io::BufReader::with_capacity(4, "(()(()(\n".as_bytes());
+        assert_eq!(super::part_one_reader(input).unwrap(), 3);
+        let input = "()())".as_bytes().chain(std::io::repeat(b'x'));
+        let output = super::part_two_reader(std::io::BufReader::new(input)).unwrap();
+        assert_eq!(output, Some(5));
+    }
 }
@@ -39,33 +39,289 @@
 // 
 // How many total feet of ribbon should they order?
 
-use std::{path::Path, fs::File, io::{BufRead, BufReader}};
+use std::fmt::Display;
 
+use serde_json::{json, Value};
 
-fn load_input() -> std::io::Result<Vec<(usize, usize, usize)>> {
-    let mod_path = Path::new(file!())
-        .parent()
-        .expect("the module file has no parent path")
-        .join("input.txt");
-    let file = File::open(mod_path)?;
-    let reader = BufReader::new(file);
-    let out = reader
+use crate::{error::{Error, Result}, solution::Solution};
+
+
+/// A box-shaped present, in feet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Present {
+    pub l: usize,
+    pub w: usize,
+    pub h: usize,
+}
+
+
+fn overflow() -> Error {
+    Error::semantic("wrapping needs overflow")
+}
+
+
+fn mul(a: usize, b: usize) -> Result<usize> {
+    a.checked_mul(b).ok_or_else(overflow)
+}
+
+
+/// Sums `values`, failing instead of wrapping around on overflow.
+pub fn checked_sum(values: impl IntoIterator<Item = Result<usize>>) -> Result<usize> {
+    values.into_iter().try_fold(0_usize, |total, value| {
+        total.checked_add(value?).ok_or_else(overflow)
+    })
+}
+
+
+/// How a kind of present is wrapped. All lengths are in feet.
+pub trait Wrap {
+    /// The sheets of paper the present is wrapped in, as width by length.
+    fn paper_pieces(&self) -> Result<Vec<(usize, usize)>>;
+
+    /// The lengths of ribbon the present takes.
+    fn ribbon_pieces(&self) -> Result<Vec<usize>>;
+
+    /// Square feet of wrapping paper.
+    fn paper(&self) -> Result<usize> {
+        checked_sum(self.paper_pieces()?.into_iter().map(|(a, b)| mul(a, b)))
+    }
+
+    /// Feet of ribbon.
+    fn ribbon(&self) -> Result<usize> {
+        checked_sum(self.ribbon_pieces()?.into_iter().map(Ok))
+    }
+}
+
+
+/// Two of each side, then the smallest side again as slack; and ribbon for the smallest
+/// perimeter of any face plus the volume for the bow.
+impl Wrap for Present {
+    fn paper_pieces(&self) -> Result<Vec<(usize, usize)>> {
+        let sides = [(self.l, self.w), (self.w, self.h), (self.h, self.l)];
+        let slack = sides.into_iter()
+            .min_by_key(|(a, b)| a.saturating_mul(*b))
+            .unwrap_or_default();
+        Ok(vec![sides[0], sides[0], sides[1], sides[1], sides[2], sides[2], slack])
+    }
+
+    fn ribbon_pieces(&self) -> Result<Vec<usize>> {
+        let (l, w, h) = (self.l, self.w, self.h);
+        let half = [l.checked_add(w), w.checked_add(h), h.checked_add(l)]
+            .into_iter()
+            .min()
+            .flatten()
+            .ok_or_else(overflow)?;
+        Ok(vec![mul(half, 2)?, mul(mul(l, w)?, h)?])
+    }
+}
+
+
+impl Display for Present {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}x{}", self.l, self.w, self.h)
+    }
+}
+
+
+/// Parses `n` numbers separated by `x`s or commas, where `text` starts at `column`.
+fn parse_values(text: &str, column: usize, n: usize) -> Result<Vec<usize>> {
+    let separator = if text.contains(',') { ',' } else { 'x' };
+    let mut vec = Vec::with_capacity(n);
+    let mut column = column;
+    for field in text.split(separator) {
+        let value = field.trim();
+        let start = column + field.chars().take_while(|c| c.is_whitespace()).count();
+        let value = value
+            .parse::<usize>()
+            .map_err(|e| Error::parse(1, start, format!("{e}: {value:?}")))?;
+        vec.push(value);
+        column += field.chars().count() + 1;
+    }
+    if vec.len() != n {
+        let count = |n: usize| match n {
+            1 => String::from("one value"),
+            2 => String::from("two values"),
+            3 => String::from("three values"),
+            n => format!("{n} values"),
+        };
+        let message = format!("expected {}, found {}", count(n), vec.len());
+        return Err(Error::parse(1, 1, message))
+    }
+    Ok(vec)
+}
+
+
+/// Accepts `LxWxH`, with or without spaces around the `x`s, or `L,W,H`.
+impl TryFrom<&str> for Present {
+    type Error = Error;
+
+    fn try_from(line: &str) -> Result<Self> {
+        let vec = parse_values(line, 1, 3)?;
+        Ok(Present { l: vec[0], w: vec[1], h: vec[2] })
+    }
+}
+
+
+/// A present of any shape the elves ship. Round shapes use 355/113 for pi and round
+/// lengths up to the next foot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shape {
+    Box(Present),
+    Cube { side: usize },
+    /// A closed can, wrapped like a box: a sheet around the side, one over each end,
+    /// then the smallest of those again as slack. Ribbon goes around it lengthwise, plus
+    /// the volume for the bow.
+    Cylinder { diameter: usize, height: usize },
+    /// A tube with its ends twisted shut: one sheet around it, half a diameter longer
+    /// at each end, and a tie of ribbon around each end.
+    Tube { diameter: usize, length: usize },
+}
+
+
+fn circumference(diameter: usize) -> Result<usize> {
+    Ok(mul(diameter, 355)?.div_ceil(113))
+}
+
+
+impl Wrap for Shape {
+    fn paper_pieces(&self) -> Result<Vec<(usize, usize)>> {
+        match *self {
+            Shape::Box(present) => present.paper_pieces(),
+            Shape::Cube { side } => Present { l: side, w: side, h: side }.paper_pieces(),
+            Shape::Cylinder { diameter, height } => {
+                let side = (circumference(diameter)?, height);
+                let end = (diameter, diameter);
+                let slack = match mul(side.0, side.1)? < mul(end.0, end.1)? {
+                    true => side,
+                    false => end,
+                };
+                Ok(vec![side, end, end, slack])
+            },
+            Shape::Tube { diameter, length } => {
+                let length = length.checked_add(diameter).ok_or_else(overflow)?;
+                Ok(vec![(circumference(diameter)?, length)])
+            },
+        }
+    }
+
+    fn ribbon_pieces(&self) -> Result<Vec<usize>> {
+        match *self {
+            Shape::Box(present) => present.ribbon_pieces(),
+            Shape::Cube { side } => Present { l: side, w: side, h: side }.ribbon_pieces(),
+            Shape::Cylinder { diameter, height } => {
+                let around = mul(diameter.checked_add(height).ok_or_else(overflow)?, 2)?;
+                let volume = mul(mul(mul(diameter, diameter)?, height)?, 355)?;
+                Ok(vec![around, volume.div_ceil(4 * 113)])
+            },
+            Shape::Tube { diameter, .. } => {
+                let tie = circumference(diameter)?;
+                Ok(vec![tie, tie])
+            },
+        }
+    }
+}
+
+
+impl From<Present> for Shape {
+    fn from(value: Present) -> Self {
+        Shape::Box(value)
+    }
+}
+
+
+impl Display for Shape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Shape::Box(present) => write!(f, "{present}"),
+            Shape::Cube { side } => write!(f, "cube {side}"),
+            Shape::Cylinder { diameter, height } => {
+                write!(f, "cylinder {diameter}x{height}")
+            },
+            Shape::Tube { diameter, length } => write!(f, "tube {diameter}x{length}"),
+        }
+    }
+}
+
+
+/// A shape name followed by its dimensions: `box LxWxH`, `cube S`, `cylinder DxH` or
+/// `tube DxL`, with the dimensions in any syntax [`Present`] accepts. Without a name
+/// the line is a box.
+impl TryFrom<&str> for Shape {
+    type Error = Error;
+
+    fn try_from(line: &str) -> Result<Self> {
+        if !line.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Present::try_from(line).map(Shape::Box)
+        }
+        let name_len = line.find(char::is_whitespace).unwrap_or(line.len());
+        let (name, dims) = line.split_at(name_len);
+        let column = name.chars().count() + 1;
+        let shape = match name {
+            "box" => {
+                let vec = parse_values(dims, column, 3)?;
+                Shape::Box(Present { l: vec[0], w: vec[1], h: vec[2] })
+            },
+            "cube" => Shape::Cube { side: parse_values(dims, column, 1)?[0] },
+            "cylinder" => {
+                let vec = parse_values(dims, column, 2)?;
+                Shape::Cylinder { diameter: vec[0], height: vec[1] }
+            },
+            "tube" => {
+                let vec = parse_values(dims, column, 2)?;
+                Shape::Tube { diameter: vec[0], length: vec[1] }
+            },
+            _ => return Err(Error::parse(1, 1, format!("unknown shape {name:?}"))),
+        };
+        Ok(shape)
+    }
+}
+
+
+/// A CSV header row, e.g. `l,w,h`: commas and no digits.
+fn is_header(line: &str) -> bool {
+    line.contains(',') && !line.chars().any(|c| c.is_ascii_digit())
+}
+
+
+/// Parses one item per line, skipping blank lines and a leading CSV header row.
+fn parse_lines<'a, T>(input: &'a str) -> Result<Vec<T>>
+where
+    T: TryFrom<&'a str, Error = Error>,
+{
+    input
         .lines()
-        .map(|line| {
-            let vec = line
-                .unwrap()
-                .split("x")
-                .map(|n| n.parse::<usize>().unwrap())
-                .collect::<Vec<usize>>();
-            if vec.len() != 3 { panic!("there should be three values in every line") }
-            (vec[0], vec[1], vec[2])
-        })
-        .collect();
-    Ok(out)
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .enumerate()
+        .filter(|(n, (_, line))| !(*n == 0 && is_header(line)))
+        .map(|(_, (i, line))| T::try_from(line.trim_end()).map_err(|e| e.on_line(i + 1)))
+        .collect()
 }
 
 
-fn part_1(input: &[(usize, usize, usize)]) -> usize {
+/// Parses one box per line, in any syntax [`Present`] accepts. Blank lines and a
+/// leading CSV header row are skipped.
+pub fn parse_presents(input: &str) -> Result<Vec<Present>> {
+    parse_lines(input)
+}
+
+
+/// Parses one present of any shape per line, in the syntax [`Shape`] accepts. Blank
+/// lines and a leading CSV header row are skipped.
+pub fn parse_shapes(input: &str) -> Result<Vec<Shape>> {
+    parse_lines(input)
+}
+
+
+/// Parses one `LxWxH` present per line; blank lines are skipped.
+pub fn parse_dimensions(input: &str) -> Result<Vec<(usize, usize, usize)>> {
+    let presents = parse_presents(input)?;
+    Ok(presents.into_iter().map(|p| (p.l, p.w, p.h)).collect())
+}
+
+
+/// Total square feet of wrapping paper, slack included.
+pub fn part_1(input: &[(usize, usize, usize)]) -> usize {
     let mut total_area = 0;
     for (l, w, h) in input {
         let a_min = (l*w).min(l*h).min(w*h);
@@ -75,7 +331,8 @@ fn part_1(input: &[(usize, usize, usize)]) -> usize {
 }
 
 
-fn part_2(input: &[(usize, usize, usize)]) -> usize {
+/// Total feet of ribbon, bows included.
+pub fn part_2(input: &[(usize, usize, usize)]) -> usize {
     let mut total_len = 0;
     for (l, w, h) in input {
         let p_min = (2*(l+w)).min(2*(l+h)).min(2*(w+h));
@@ -85,19 +342,645 @@ fn part_2(input: &[(usize, usize, usize)]) -> usize {
     total_len
 }
 
+
+/// What one present on the list needs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Item {
+    /// 1-based position in the list.
+    pub index: usize,
+    pub present: Shape,
+    pub paper: usize,
+    pub ribbon: usize,
+}
+
+
+/// The order for a whole list of presents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Report {
+    pub paper: usize,
+    pub ribbon: usize,
+    pub items: Vec<Item>,
+}
+
+
+impl Report {
+    pub fn new(presents: &[Shape]) -> Result<Self> {
+        let items: Vec<Item> = presents.iter()
+            .enumerate()
+            .map(|(i, &present)| Ok(Item {
+                index: i + 1,
+                present,
+                paper: present.paper()?,
+                ribbon: present.ribbon()?,
+            }))
+            .collect::<Result<_>>()?;
+        let paper = checked_sum(items.iter().map(|item| Ok(item.paper)))?;
+        let ribbon = checked_sum(items.iter().map(|item| Ok(item.ribbon)))?;
+        Ok(Report { paper, ribbon, items })
+    }
+
+    /// The `n` presents needing the most paper, most first; ties keep list order.
+    pub fn top_paper(&self, n: usize) -> Vec<&Item> {
+        self.top_by(n, |item| item.paper)
+    }
+
+    /// The `n` presents needing the most ribbon, most first; ties keep list order.
+    pub fn top_ribbon(&self, n: usize) -> Vec<&Item> {
+        self.top_by(n, |item| item.ribbon)
+    }
+
+    fn top_by(&self, n: usize, key: impl Fn(&Item) -> usize) -> Vec<&Item> {
+        let mut items: Vec<&Item> = self.items.iter().collect();
+        items.sort_by_key(|item| std::cmp::Reverse(key(item)));
+        items.truncate(n);
+        items
+    }
+
+    pub fn to_json(&self) -> Value {
+        let items: Vec<Value> = self.items.iter()
+            .map(|item| json!({
+                "index": item.index,
+                "present": item.present.to_string(),
+                "paper": item.paper,
+                "ribbon": item.ribbon,
+            }))
+            .collect();
+        json!({ "paper": self.paper, "ribbon": self.ribbon, "presents": items })
+    }
+
+    /// The per-present breakdown, with a header row.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("index,present,paper,ribbon\n");
+        for item in &self.items {
+            let (index, present) = (item.index, item.present);
+            let (paper, ribbon) = (item.paper, item.ribbon);
+            csv.push_str(&format!("{index},{present},{paper},{ribbon}\n"));
+        }
+        csv
+    }
+}
+
+
+/// A roll of wrapping paper, in feet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Roll {
+    pub width: usize,
+    pub length: usize,
+}
+
+
+/// Where a sheet of paper is cut from. `x` runs across the roll and `y` along it; the
+/// sheet may be turned, so `width` and `length` are as cut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PaperCut {
+    /// 1-based position of the present in the list.
+    pub present: usize,
+    pub roll: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub length: usize,
+}
+
+
+/// Where a length of ribbon is cut from, `offset` feet into the spool.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RibbonCut {
+    /// 1-based position of the present in the list.
+    pub present: usize,
+    pub spool: usize,
+    pub offset: usize,
+    pub length: usize,
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Plan {
+    pub rolls: usize,
+    pub spools: usize,
+    pub paper_cuts: Vec<PaperCut>,
+    pub ribbon_cuts: Vec<RibbonCut>,
+    /// Percent of the ordered paper that isn't cut into sheets.
+    pub paper_waste: f64,
+    /// Percent of the ordered ribbon that isn't cut into lengths.
+    pub ribbon_waste: f64,
+}
+
+
+/// A row of sheets across a roll, as tall as its first (tallest) sheet.
+struct Shelf {
+    y: usize,
+    length: usize,
+    used: usize,
+}
+
+
+fn waste(used: usize, ordered: usize) -> f64 {
+    match ordered {
+        0 => 0.0,
+        _ => (ordered - used) as f64 / ordered as f64 * 100.0,
+    }
+}
+
+
+/// Packs the sheets onto rolls first-fit decreasing by shelves: sheets are turned so
+/// their longer side runs across the roll where it fits, then placed tallest first on
+/// the first shelf with room, else on a new shelf at the end of the first roll with room,
+/// else on a new roll. Sheets with no area need no paper and get no cut.
+fn pack_paper<W: Wrap>(presents: &[W], roll: Roll) -> Result<Vec<PaperCut>> {
+    let mut sheets = Vec::new();
+    for (i, present) in presents.iter().enumerate() {
+        for (a, b) in present.paper_pieces()? {
+            if a == 0 || b == 0 {
+                continue
+            }
+            let (long, short) = (a.max(b), a.min(b));
+            let (width, length) = match long <= roll.width {
+                true => (long, short),
+                false => (short, long),
+            };
+            if width > roll.width || length > roll.length {
+                return Err(Error::semantic(format!(
+                    "a {a}x{b} sheet for present {} doesn't fit on a {}x{} roll",
+                    i + 1, roll.width, roll.length,
+                )))
+            }
+            sheets.push((i + 1, width, length));
+        }
+    }
+    sheets.sort_by(|a, b| b.2.cmp(&a.2).then(b.1.cmp(&a.1)));
+    let mut rolls: Vec<Vec<Shelf>> = Vec::new();
+    let mut cuts = Vec::with_capacity(sheets.len());
+    for (present, width, length) in sheets {
+        let shelf = rolls.iter_mut()
+            .enumerate()
+            .flat_map(|(r, shelves)| shelves.iter_mut().map(move |shelf| (r, shelf)))
+            .find(|(_, shelf)| shelf.length >= length && shelf.used + width <= roll.width);
+        let (r, x, y) = match shelf {
+            Some((r, shelf)) => {
+                shelf.used += width;
+                (r, shelf.used - width, shelf.y)
+            },
+            None => {
+                let end = |shelves: &[Shelf]| shelves.last().map_or(0, |s| s.y + s.length);
+                let r = match rolls.iter().position(|s| end(s) + length <= roll.length) {
+                    Some(r) => r,
+                    None => {
+                        rolls.push(Vec::new());
+                        rolls.len() - 1
+                    },
+                };
+                let y = end(&rolls[r]);
+                rolls[r].push(Shelf { y, length, used: width });
+                (r, 0, y)
+            },
+        };
+        cuts.push(PaperCut { present, roll: r + 1, x, y, width, length });
+    }
+    cuts.sort_by_key(|cut| (cut.present, cut.roll, cut.y, cut.x));
+    Ok(cuts)
+}
+
+
+/// Packs the lengths onto spools first-fit decreasing, skipping any of no length.
+fn pack_ribbon<W: Wrap>(presents: &[W], spool: usize) -> Result<Vec<RibbonCut>> {
+    let mut lengths = Vec::new();
+    for (i, present) in presents.iter().enumerate() {
+        for length in present.ribbon_pieces()? {
+            if length == 0 {
+                continue
+            }
+            if length > spool {
+                let present = i + 1;
+                return Err(Error::semantic(format!(
+                    "present {present} needs {length} feet of ribbon, more than a spool"
+                )))
+            }
+            lengths.push((i + 1, length));
+        }
+    }
+    lengths.sort_by_key(|&(_, length)| std::cmp::Reverse(length));
+    let mut used: Vec<usize> = Vec::new();
+    let mut cuts = Vec::with_capacity(lengths.len());
+    for (present, length) in lengths {
+        let s = match used.iter().position(|&u| u + length <= spool) {
+            Some(s) => s,
+            None => {
+                used.push(0);
+                used.len() - 1
+            },
+        };
+        cuts.push(RibbonCut { present, spool: s + 1, offset: used[s], length });
+        used[s] += length;
+    }
+    cuts.sort_by_key(|cut| (cut.present, cut.spool, cut.offset));
+    Ok(cuts)
+}
+
+
+/// Works out how many rolls of paper and spools of ribbon to order for `presents`, and
+/// where to cut each present's sheets and lengths from.
+pub fn plan<W: Wrap>(presents: &[W], roll: Roll, spool: usize) -> Result<Plan> {
+    if roll.width == 0 || roll.length == 0 || spool == 0 {
+        return Err(Error::semantic("rolls and spools must not be empty"))
+    }
+    let paper_cuts = pack_paper(presents, roll)?;
+    let ribbon_cuts = pack_ribbon(presents, spool)?;
+    let rolls = paper_cuts.iter().map(|cut| cut.roll).max().unwrap_or(0);
+    let spools = ribbon_cuts.iter().map(|cut| cut.spool).max().unwrap_or(0);
+    let paper = checked_sum(presents.iter().map(W::paper))?;
+    let ribbon = checked_sum(presents.iter().map(W::ribbon))?;
+    Ok(Plan {
+        rolls,
+        spools,
+        paper_cuts,
+        ribbon_cuts,
+        paper_waste: waste(paper, mul(rolls, mul(roll.width, roll.length)?)?),
+        ribbon_waste: waste(ribbon, mul(spools, spool)?),
+    })
+}
+
+
+/// A present on a wish list, with how much it matters that it gets wrapped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Wish {
+    pub shape: Shape,
+    pub priority: usize,
+}
+
+
+/// A present in the syntax [`Shape`] accepts, optionally followed by `@` and its
+/// priority, e.g. `cube 3 @ 5`. The priority defaults to 1.
+impl TryFrom<&str> for Wish {
+    type Error = Error;
+
+    fn try_from(line: &str) -> Result<Self> {
+        let Some((shape, priority)) = line.split_once('@') else {
+            return Ok(Wish { shape: Shape::try_from(line.trim_end())?, priority: 1 })
+        };
+        let column = shape.chars().count() + 2;
+        let value = priority.trim();
+        let priority = value.parse::<usize>().map_err(|e| {
+            let start = column + priority.chars().take_while(|c| c.is_whitespace()).count();
+            Error::parse(1, start, format!("{e}: {value:?}"))
+        })?;
+        Ok(Wish { shape: Shape::try_from(shape.trim_end())?, priority })
+    }
+}
+
+
+/// Parses one [`Wish`] per line. Blank lines and a leading CSV header row are skipped.
+pub fn parse_wishes(input: &str) -> Result<Vec<Wish>> {
+    parse_lines(input)
+}
+
+
+/// How much paper and ribbon there is to wrap with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Budget {
+    pub paper: usize,
+    pub ribbon: usize,
+}
+
+
+/// The presents chosen to be wrapped within a [`Budget`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selection {
+    /// 1-based positions in the list, in order.
+    pub presents: Vec<usize>,
+    /// The sum of the chosen presents' priorities.
+    pub value: usize,
+    pub paper: usize,
+    pub ribbon: usize,
+    /// Whether the selection is known to be the best possible, rather than a heuristic
+    /// one.
+    pub exact: bool,
+}
+
+
+/// The most table cells, summed over all presents, the exact search may use.
+pub const EXACT_CELLS: usize = 1 << 24;
+
+
+/// The paper, ribbon and priority of each present.
+type Costs = Vec<(usize, usize, usize)>;
+
+
+/// Exact 0/1 knapsack over both budgets, with one table cell per amount of paper and
+/// ribbon; `paper` and `ribbon` must already be capped at what the presents could use.
+fn select_exact(costs: &Costs, paper: usize, ribbon: usize) -> Vec<usize> {
+    let cells = (paper + 1) * (ribbon + 1);
+    let mut best = vec![0_usize; cells];
+    let mut taken = vec![false; costs.len() * cells];
+    for (i, &(p, r, value)) in costs.iter().enumerate() {
+        for a in (p..=paper).rev() {
+            for b in (r..=ribbon).rev() {
+                let with = best[(a - p) * (ribbon + 1) + b - r] + value;
+                if with > best[a * (ribbon + 1) + b] {
+                    best[a * (ribbon + 1) + b] = with;
+                    taken[i * cells + a * (ribbon + 1) + b] = true;
+                }
+            }
+        }
+    }
+    let (mut a, mut b) = (paper, ribbon);
+    let mut chosen = Vec::new();
+    for (i, &(p, r, _)) in costs.iter().enumerate().rev() {
+        if taken[i * cells + a * (ribbon + 1) + b] {
+            chosen.push(i);
+            (a, b) = (a - p, b - r);
+        }
+    }
+    chosen
+}
+
+
+/// Takes presents greedily by priority per share of the budget they use, then keeps
+/// whichever is better of that and the single most valuable present.
+fn select_greedy(costs: &Costs, budget: Budget) -> Vec<usize> {
+    let share = |&(p, r, _): &(usize, usize, usize)| {
+        p as f64 / budget.paper.max(1) as f64 + r as f64 / budget.ribbon.max(1) as f64
+    };
+    let mut order: Vec<usize> = (0..costs.len()).collect();
+    order.sort_by(|&i, &j| {
+        let (a, b) = (&costs[i], &costs[j]);
+        (b.2 as f64 * share(a)).total_cmp(&(a.2 as f64 * share(b))).then(i.cmp(&j))
+    });
+    let (mut paper, mut ribbon) = (budget.paper, budget.ribbon);
+    let mut chosen = Vec::new();
+    for i in order {
+        let (p, r, _) = costs[i];
+        if p <= paper && r <= ribbon {
+            (paper, ribbon) = (paper - p, ribbon - r);
+            chosen.push(i);
+        }
+    }
+    let value = |chosen: &[usize]| chosen.iter().map(|&i| costs[i].2).sum::<usize>();
+    let single = (0..costs.len()).max_by_key(|&i| (costs[i].2, std::cmp::Reverse(i)));
+    match single {
+        Some(i) if costs[i].2 > value(&chosen) => vec![i],
+        _ => chosen,
+    }
+}
+
+
+/// Chooses which presents to wrap within `budget` so the sum of their priorities is
+/// as large as possible. Small inputs are solved exactly; when the search would need
+/// more than [`EXACT_CELLS`] table cells a greedy heuristic is used instead.
+pub fn select<W: Wrap>(
+    presents: &[W],
+    priorities: &[usize],
+    budget: Budget,
+) -> Result<Selection> {
+    if presents.len() != priorities.len() {
+        return Err(Error::semantic("every present needs exactly one priority"))
+    }
+    checked_sum(priorities.iter().copied().map(Ok))?;
+    let mut fitting = Vec::new();
+    let mut costs = Costs::new();
+    for (i, (present, &priority)) in presents.iter().zip(priorities).enumerate() {
+        let (paper, ribbon) = (present.paper()?, present.ribbon()?);
+        if paper <= budget.paper && ribbon <= budget.ribbon && priority > 0 {
+            fitting.push(i);
+            costs.push((paper, ribbon, priority));
+        }
+    }
+    let paper = budget.paper.min(checked_sum(costs.iter().map(|c| Ok(c.0)))?);
+    let ribbon = budget.ribbon.min(checked_sum(costs.iter().map(|c| Ok(c.1)))?);
+    let cells = (paper.checked_add(1), ribbon.checked_add(1));
+    let cells = match cells {
+        (Some(p), Some(r)) => p.checked_mul(r).and_then(|c| c.checked_mul(costs.len())),
+        _ => None,
+    };
+    let exact = cells.is_some_and(|cells| cells <= EXACT_CELLS);
+    let mut chosen = match exact {
+        true => select_exact(&costs, paper, ribbon),
+        false => select_greedy(&costs, budget),
+    };
+    chosen.sort();
+    let sum = |f: fn(&(usize, usize, usize)) -> usize| {
+        chosen.iter().map(|&i| f(&costs[i])).sum::<usize>()
+    };
+    Ok(Selection {
+        presents: chosen.iter().map(|&i| fitting[i] + 1).collect(),
+        value: sum(|c| c.2),
+        paper: sum(|c| c.0),
+        ribbon: sum(|c| c.1),
+        exact,
+    })
+}
+
+
+/// The largest number of `presents` that can be wrapped within `budget`.
+pub fn most_presents<W: Wrap>(presents: &[W], budget: Budget) -> Result<Selection> {
+    select(presents, &vec![1; presents.len()], budget)
+}
+
+
+/// The selection from a wish list whose priorities add up to the most.
+pub fn select_wishes(wishes: &[Wish], budget: Budget) -> Result<Selection> {
+    let shapes: Vec<Shape> = wishes.iter().map(|wish| wish.shape).collect();
+    let priorities: Vec<usize> = wishes.iter().map(|wish| wish.priority).collect();
+    select(&shapes, &priorities, budget)
+}
+
+
+pub struct Day02;
+
+
+impl Solution for Day02 {
+    const DAY: u8 = 2;
+    const TITLE: &'static str = "I Was Told There Would Be No Math";
+
+    type Input = Vec<Shape>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        parse_shapes(input)
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        checked_sum(input.iter().map(Shape::paper))
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        checked_sum(input.iter().map(Shape::ribbon))
+    }
+}
+
+
 #[cfg(test)]
 mod test {
+    use super::{Shape, Wrap};
+    use crate::input::load_day;
+
     #[test]
     fn part_1() {
-        let input = super::load_input().unwrap();
+        let input = super::parse_dimensions(&load_day(2).unwrap()).unwrap();
         let output = super::part_1(&input);
         assert_eq!(output, 1606483);
     }
 
     #[test]
     fn part_2() {
-        let input = super::load_input().unwrap();
+        let input = super::parse_dimensions(&load_day(2).unwrap()).unwrap();
         let output = super::part_2(&input);
         assert_eq!(output, 3842356);
     }
+
+    #[test]
+    fn malformed() {
+        let e = super::parse_dimensions("2x3x4\n1x1\n").unwrap_err();
+        let expected = "parse error at line 2, column 1: expected three values, found 2";
+        assert_eq!(e.to_string(), expected);
+        let e = super::parse_dimensions("2x3x4\r\n1xAx1\r\n").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 2, column 3:"));
+    }
+
+    #[test]
+    fn syntaxes() {
+        let output = super::parse_presents("2x3x4\n2 x 3 x 4\n\n2, 3, 4\n").unwrap();
+        assert_eq!(output, vec![super::Present { l: 2, w: 3, h: 4 }; 3]);
+        let output = super::parse_presents("l,w,h\n1,1,10\n").unwrap();
+        assert_eq!(output, vec![super::Present { l: 1, w: 1, h: 10 }]);
+        let e = super::parse_presents("2 x  y x 4").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 1, column 6:"), "{e}");
+    }
+
+    #[test]
+    fn overflow() {
+        let present = super::Present { l: usize::MAX, w: 2, h: 1 };
+        assert!(present.paper().is_err());
+        assert!(present.ribbon().is_err());
+    }
+
+    #[test]
+    fn plan() {
+        let presents = super::parse_presents("2x3x4\n1x1x10\n").unwrap();
+        let roll = super::Roll { width: 4, length: 20 };
+        let plan = super::plan(&presents, roll, 25).unwrap();
+        assert_eq!((plan.rolls, plan.spools), (2, 2));
+        assert_eq!(plan.paper_cuts.len(), 14);
+        assert_eq!(plan.ribbon_cuts.iter().map(|c| c.length).sum::<usize>(), 48);
+        assert!((plan.paper_waste - (160.0 - 101.0) / 160.0 * 100.0).abs() < 1e-9);
+        assert!((plan.ribbon_waste - 4.0).abs() < 1e-9);
+        for (i, a) in plan.paper_cuts.iter().enumerate() {
+            assert!(a.x + a.width <= roll.width && a.y + a.length <= roll.length);
+            for b in &plan.paper_cuts[i + 1..] {
+                let apart = a.roll != b.roll
+                    || a.x + a.width <= b.x || b.x + b.width <= a.x
+                    || a.y + a.length <= b.y || b.y + b.length <= a.y;
+                assert!(apart, "{a:?} overlaps {b:?}");
+            }
+        }
+        assert!(super::plan(&presents, super::Roll { width: 3, length: 3 }, 25).is_err());
+        assert!(super::plan(&presents, roll, 20).is_err());
+        let presents = super::parse_shapes("cube 0\ntube 0x0\n").unwrap();
+        let plan = super::plan(&presents, roll, 25).unwrap();
+        assert_eq!((plan.rolls, plan.spools, plan.paper_waste), (0, 0, 0.0));
+        assert!(plan.paper_cuts.is_empty() && plan.ribbon_cuts.is_empty());
+    }
+
+    #[test]
+    fn report() {
+        let presents = super::parse_shapes("2x3x4\n1x1x10\n").unwrap();
+        let report = super::Report::new(&presents).unwrap();
+        assert_eq!((report.paper, report.ribbon), (101, 48));
+        assert_eq!(report.top_paper(1)[0].index, 1);
+        assert_eq!(report.top_ribbon(5).len(), 2);
+        let expected = "index,present,paper,ribbon\n1,2x3x4,58,34\n2,1x1x10,43,14\n";
+        assert_eq!(report.to_csv(), expected);
+        assert_eq!(report.to_json()["presents"][1]["paper"], 43);
+    }
+
+    #[test]
+    fn shapes() {
+        let input = "box 2x3x4\ncube 2\ncylinder 2x3\ntube 1 x 4\n1,1,10\n";
+        let shapes = super::parse_shapes(input).unwrap();
+        let names: Vec<String> = shapes.iter().map(Shape::to_string).collect();
+        assert_eq!(names, ["2x3x4", "cube 2", "cylinder 2x3", "tube 1x4", "1x1x10"]);
+        assert_eq!(shapes[1].paper().unwrap(), 28);
+        assert_eq!(shapes[1].ribbon().unwrap(), 16);
+        assert_eq!(shapes[2].paper_pieces().unwrap(), [(7, 3), (2, 2), (2, 2), (2, 2)]);
+        assert_eq!(shapes[2].ribbon().unwrap(), 10 + 10);
+        assert_eq!(shapes[3].paper().unwrap(), 4 * 5);
+        assert_eq!(shapes[3].ribbon().unwrap(), 8);
+        let e = super::parse_shapes("cube 1\nsphere 3\n").unwrap_err();
+        let expected = "parse error at line 2, column 1: unknown shape \"sphere\"";
+        assert_eq!(e.to_string(), expected);
+        let e = super::parse_shapes("tube 2x3x4").unwrap_err();
+        assert!(e.to_string().ends_with("expected two values, found 3"), "{e}");
+        let e = super::parse_shapes("cylinder 2xz").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 1, column 12:"), "{e}");
+        let roll = super::Roll { width: 10, length: 100 };
+        let plan = super::plan(&shapes, roll, 50).unwrap();
+        assert_eq!(plan.paper_cuts.len(), 7 + 7 + 4 + 1 + 7);
+    }
+
+    #[test]
+    fn wishes() {
+        let wishes = super::parse_wishes("2x3x4 @ 5\ncube 2\n1x1x10 @3\n").unwrap();
+        assert_eq!(wishes[1], super::Wish { shape: Shape::Cube { side: 2 }, priority: 1 });
+        assert_eq!(wishes[2].priority, 3);
+        let e = super::parse_wishes("1x1x1\ncube 2 @ high\n").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 2, column 10:"), "{e}");
+    }
+
+    #[test]
+    fn select() {
+        // Papers 58, 28 and 43; ribbons 34, 16 and 14.
+        let wishes = super::parse_wishes("2x3x4 @ 5\ncube 2\n1x1x10 @ 3\n").unwrap();
+        let budget = super::Budget { paper: 101, ribbon: 48 };
+        let output = super::select_wishes(&wishes, budget).unwrap();
+        assert_eq!((output.presents, output.value), (vec![1, 3], 8));
+        assert!(output.exact);
+        let shapes: Vec<Shape> = wishes.iter().map(|wish| wish.shape).collect();
+        let budget = super::Budget { paper: 75, ribbon: 40 };
+        let output = super::most_presents(&shapes, budget).unwrap();
+        assert_eq!((output.presents, output.paper, output.ribbon), (vec![2, 3], 71, 30));
+        let output = super::select_wishes(&wishes, budget).unwrap();
+        assert_eq!((output.presents, output.value), (vec![1], 5));
+        let budget = super::Budget { paper: 10, ribbon: 100 };
+        assert!(super::select_wishes(&wishes, budget).unwrap().presents.is_empty());
+        assert!(super::select(&shapes, &[1], budget).is_err());
+    }
+
+    #[test]
+    fn select_heuristic() {
+        let mut seed = 7_u64;
+        let mut next = |n: u64| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (seed >> 33) % n + 1
+        };
+        for _ in 0..20 {
+            let shapes: Vec<Shape> = (0..8)
+                .map(|_| Shape::Box(super::Present {
+                    l: next(4) as usize, w: next(4) as usize, h: next(4) as usize
+                }))
+                .collect();
+            let priorities: Vec<usize> = (0..8).map(|_| next(9) as usize).collect();
+            let budget = super::Budget { paper: 150, ribbon: 90 };
+            let exact = super::select(&shapes, &priorities, budget).unwrap();
+            let costs: super::Costs = shapes.iter()
+                .zip(&priorities)
+                .map(|(s, &v)| (s.paper().unwrap(), s.ribbon().unwrap(), v))
+                .collect();
+            let greedy = super::select_greedy(&costs, budget);
+            let value: usize = greedy.iter().map(|&i| priorities[i]).sum();
+            assert!(exact.exact && value <= exact.value);
+            assert!(exact.paper <= budget.paper && exact.ribbon <= budget.ribbon);
+        }
+        // Papers 240000, 17500 and 16300; ribbons 6000600, 125200 and 40040. Not all
+        // three fit, and the exact table would need 3 * 273801 * 6130001 cells.
+        let shapes = super::parse_shapes("100x200x300
+50x50x50
+10x10x400
+").unwrap();
+        let budget = super::Budget { paper: 300_000, ribbon: 6_130_000 };
+        let output = super::select(&shapes, &[3, 2, 9], budget).unwrap();
+        assert!(!output.exact);
+        assert_eq!((output.presents, output.value), (vec![2, 3], 11));
+        assert!(output.paper <= budget.paper && output.ribbon <= budget.ribbon);
+    }
 }
@@ -19,76 +19,924 @@
 //!   starting/ending location.
 //! - ^v^v^v^v^v delivers a bunch of presents to some very lucky children at only 2 houses.
 
-use std::{collections::HashSet, path::Path};
-
-
-fn part_1(input: &str) -> usize {
-    let mut coords: HashSet<(isize, isize)> = HashSet::new();
-    let mut row: isize = 0;
-    let mut col: isize = 0;
-    coords.insert((row, col));
-    for arrow in input.chars() {
-        match arrow {
-            '^' => row += 1,
-            'v' => row -= 1,
-            '>' => col += 1,
-            '<' => col -= 1,
-            '\n' => {},
-            _ => unreachable!("unexpected char found")
+use std::{
+    collections::HashMap,
+    fmt::Debug,
+    hash::Hash,
+    io::BufRead,
+    path::Path,
+};
+
+use crate::{
+    error::{Error, Result},
+    solution::Solution,
+    stream::Bytes,
+    tokens::{Mode, Skipped, TokenMap},
+};
+
+
+/// A grid of houses and the symbols for moving around it.
+pub trait Lattice {
+    type Point: Copy + Debug + Eq + Hash + Ord;
+
+    const ORIGIN: Self::Point;
+
+    /// The house one step from `point` in the direction of `symbol`, or `None` if
+    /// `symbol` isn't a direction.
+    fn step(point: Self::Point, symbol: u8) -> Option<Self::Point>;
+}
+
+
+/// The square grid of the puzzle, with `^`, `v`, `>` and `<` for north, south, east
+/// and west.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Square;
+
+
+impl Lattice for Square {
+    type Point = House;
+
+    const ORIGIN: House = (0, 0);
+
+    fn step((row, col): House, symbol: u8) -> Option<House> {
+        match symbol {
+            b'^' => Some((row + 1, col)),
+            b'v' => Some((row - 1, col)),
+            b'>' => Some((row, col + 1)),
+            b'<' => Some((row, col - 1)),
+            _ => None,
         }
-        coords.insert((row, col));
     }
-    coords.len()
 }
 
 
-fn part_2(input: &str) -> usize {
-    let mut coords: HashSet<(isize, isize)> = HashSet::new();
-    let (mut r1, mut c1) = (0_isize, 0_isize);
-    let (mut r2, mut c2) = (0_isize, 0_isize);
-    coords.insert((r1, c1));
-    for (i, arrow) in input.chars().enumerate() {
-        let (row, col) = if i % 2 == 0 {
-            (&mut r1, &mut c1)
-        } else {
-            (&mut r2, &mut c2)
+/// A grid of flat-topped hexagons in axial (q, r) coordinates, moved around with the
+/// keys around `s` on a keyboard: `w` and `s` for north and south, `q` and `e` for
+/// north-west and north-east, `a` and `d` for south-west and south-east.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Hex;
+
+
+impl Lattice for Hex {
+    type Point = (isize, isize);
+
+    const ORIGIN: (isize, isize) = (0, 0);
+
+    fn step((q, r): (isize, isize), symbol: u8) -> Option<(isize, isize)> {
+        match symbol {
+            b'w' => Some((q, r - 1)),
+            b's' => Some((q, r + 1)),
+            b'q' => Some((q - 1, r)),
+            b'e' => Some((q + 1, r - 1)),
+            b'a' => Some((q - 1, r + 1)),
+            b'd' => Some((q + 1, r)),
+            _ => None,
+        }
+    }
+}
+
+
+/// Stacked square grids, as (row, column, level): the square directions, plus `+` and
+/// `-` for up and down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cubic;
+
+
+impl Lattice for Cubic {
+    type Point = (isize, isize, isize);
+
+    const ORIGIN: (isize, isize, isize) = (0, 0, 0);
+
+    fn step((row, col, level): (isize, isize, isize), symbol: u8) -> Option<Self::Point> {
+        match symbol {
+            b'+' => Some((row, col, level + 1)),
+            b'-' => Some((row, col, level - 1)),
+            _ => Square::step((row, col), symbol).map(|(row, col)| (row, col, level)),
+        }
+    }
+}
+
+
+/// Moves `house` one step in the direction of `arrow`. Line endings (including the
+/// `\r` of CRLF files) are skipped.
+fn step<R: BufRead, L: Lattice>(
+    bytes: &Bytes<R>,
+    arrow: u8,
+    house: &mut L::Point,
+) -> Result<()> {
+    match L::step(*house, arrow) {
+        Some(next) => *house = next,
+        None if matches!(arrow, b'\n' | b'\r') => {},
+        None => return Err(bytes.unexpected(arrow)),
+    }
+    Ok(())
+}
+
+
+/// Bits per side of a [`TiledSet`] tile, as a power of two.
+const TILE_SHIFT: u32 = 6;
+
+
+/// One bit per house in a square of 64 by 64, a row per word.
+type Tile = [u64; 1 << TILE_SHIFT];
+
+
+/// A set of houses on a plane, stored as a bitmap in square tiles that are only
+/// allocated once a house in them is visited. A densely visited region costs about one
+/// bit per house, against dozens of bytes per house in a `HashSet`; but every tile is
+/// 512 bytes however few of its houses are in the set, so a sparse set, with houses far
+/// enough apart to each have a tile of their own, costs far more than a `HashSet`.
+///
+/// This is what [`visit_reader`] and so [`part_1`], [`part_2`] and their `_reader` and
+/// `_with` forms keep; a [`Walk`] records more about each house, in a `HashMap`.
+#[derive(Clone, Debug, Default)]
+pub struct TiledSet {
+    tiles: Vec<Tile>,
+    /// Where each tile is in `tiles`, by its corner shifted down by `TILE_SHIFT`.
+    index: HashMap<(isize, isize), usize>,
+    /// The tile last inserted into, since walks stay in one for many steps.
+    last: Option<((isize, isize), usize)>,
+    len: usize,
+}
+
+
+impl TiledSet {
+    pub fn new() -> Self {
+        TiledSet::default()
+    }
+
+    fn split((row, col): (isize, isize)) -> ((isize, isize), usize, u64) {
+        let mask = (1 << TILE_SHIFT) - 1;
+        let key = (row >> TILE_SHIFT, col >> TILE_SHIFT);
+        (key, (row & mask) as usize, 1 << (col & mask))
+    }
+
+    /// Adds `house`, returning whether it wasn't in the set already.
+    pub fn insert(&mut self, house: (isize, isize)) -> bool {
+        let (key, row, bit) = Self::split(house);
+        let tile = match self.last {
+            Some((last, tile)) if last == key => tile,
+            _ => {
+                let tiles = &mut self.tiles;
+                let tile = *self.index.entry(key).or_insert_with(|| {
+                    tiles.push([0; 1 << TILE_SHIFT]);
+                    tiles.len() - 1
+                });
+                self.last = Some((key, tile));
+                tile
+            },
         };
-        match arrow {
-            '^' => *row += 1,
-            'v' => *row -= 1,
-            '>' => *col += 1,
-            '<' => *col -= 1,
-            '\n' => {},
-            _ => unreachable!("unexpected char found")
+        let word = &mut self.tiles[tile][row];
+        let new = *word & bit == 0;
+        *word |= bit;
+        self.len += new as usize;
+        new
+    }
+
+    pub fn contains(&self, house: (isize, isize)) -> bool {
+        let (key, row, bit) = Self::split(house);
+        self.index.get(&key).is_some_and(|&tile| self.tiles[tile][row] & bit != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bytes allocated for the tiles and their index.
+    pub fn memory(&self) -> usize {
+        let entry = std::mem::size_of::<((isize, isize), usize)>();
+        self.tiles.capacity() * std::mem::size_of::<Tile>()
+            + hash_map_memory(self.index.capacity(), entry)
+    }
+}
+
+
+/// The bytes a `HashMap` with room for `capacity` entries of `entry` bytes allocates.
+/// Its capacity is less than its number of buckets, which is a power of two kept at
+/// most 7/8 full once there are 8, and each bucket also has a control byte, with one
+/// more group of 16 of those at the end.
+fn hash_map_memory(capacity: usize, entry: usize) -> usize {
+    let buckets = match capacity {
+        0 => return 0,
+        1..8 => capacity + 1,
+        _ => capacity / 7 * 8,
+    };
+    buckets * (entry + 1) + 16
+}
+
+
+/// The houses visited by `agents` taking turns, one move each, reading the directions
+/// from `reader` as they come. Only the set of visited houses is kept in memory.
+pub fn visit_reader(reader: impl BufRead, agents: usize) -> Result<TiledSet> {
+    if agents == 0 {
+        return Err(Error::semantic("at least one agent is needed"))
+    }
+    let mut bytes = Bytes::new(reader);
+    let mut houses = vec![Square::ORIGIN; agents];
+    let mut visited = TiledSet::new();
+    visited.insert(Square::ORIGIN);
+    let mut i = 0;
+    while let Some(arrow) = bytes.next() {
+        let arrow = arrow?;
+        if matches!(arrow, b'\n' | b'\r') {
+            continue
         }
-        coords.insert((*row, *col));
+        let house = &mut houses[i % agents];
+        step::<_, Square>(&bytes, arrow, house)?;
+        visited.insert(*house);
+        i += 1;
     }
-    coords.len()
+    Ok(visited)
 }
 
 
-fn load_input() -> std::io::Result<String> {
-    let mod_path = Path::new(file!())
-        .parent()
-        .unwrap()
-        .join("input.txt");
-    std::fs::read_to_string(mod_path)
+/// [`part_1`], reading the directions from `reader` as they come.
+pub fn part_1_reader(reader: impl BufRead) -> Result<usize> {
+    Ok(visit_reader(reader, 1)?.len())
+}
+
+
+/// [`part_2`], reading the directions from `reader` as they come.
+pub fn part_2_reader(reader: impl BufRead) -> Result<usize> {
+    Ok(visit_reader(reader, 2)?.len())
+}
+
+
+/// Houses visited at least once by Santa alone.
+pub fn part_1(input: &str) -> Result<usize> {
+    part_1_reader(input.as_bytes())
+}
+
+
+/// Houses visited at least once by Santa and Robo-Santa taking turns.
+pub fn part_2(input: &str) -> Result<usize> {
+    part_2_reader(input.as_bytes())
+}
+
+
+/// `^`, `v`, `>` and `<`, as (row, column) steps with north and east positive.
+pub fn default_tokens() -> TokenMap<(isize, isize)> {
+    TokenMap::new([("^", (1, 0)), ("v", (-1, 0)), (">", (0, 1)), ("<", (0, -1))])
+        .expect("the default tokens are distinct")
+}
+
+
+/// Houses visited at least once by `n_santas` taking turns, one token each, with the
+/// steps spelled by `tokens`; along with any characters skipped in lenient mode.
+fn visit_with(
+    input: &str,
+    tokens: &TokenMap<(isize, isize)>,
+    mode: Mode,
+    n_santas: usize,
+) -> Result<(usize, Skipped)> {
+    let mut houses = vec![(0, 0); n_santas];
+    let mut coords = TiledSet::new();
+    coords.insert((0, 0));
+    let mut scan = tokens.scan(input, mode);
+    for (i, token) in scan.by_ref().enumerate() {
+        let (row, col) = token?.value;
+        let house = &mut houses[i % n_santas];
+        *house = (house.0 + row, house.1 + col);
+        coords.insert(*house);
+    }
+    Ok((coords.len(), scan.into_skipped()))
+}
+
+
+/// [`part_1`] with the steps spelled by `tokens`.
+pub fn part_1_with(
+    input: &str,
+    tokens: &TokenMap<(isize, isize)>,
+    mode: Mode,
+) -> Result<(usize, Skipped)> {
+    visit_with(input, tokens, mode, 1)
+}
+
+
+/// [`part_2`] with the steps spelled by `tokens`. Santa and Robo-Santa take turns by
+/// token, so skipped characters don't count as a turn.
+pub fn part_2_with(
+    input: &str,
+    tokens: &TokenMap<(isize, isize)>,
+    mode: Mode,
+) -> Result<(usize, Skipped)> {
+    visit_with(input, tokens, mode, 2)
+}
+
+
+/// A house on the grid, as (row, column) with north and east positive.
+pub type House = (isize, isize);
+
+
+/// How the moves are handed out among the agents delivering presents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Assign {
+    /// The agents take turns, one move each, as Santa and Robo-Santa do.
+    #[default]
+    RoundRobin,
+    /// The moves are split into one contiguous block per agent, as evenly as possible:
+    /// blocks differ by at most one move, with the longer ones first.
+    Blocks,
+    /// Each move is preceded by the 0-based number of the agent making it, e.g. `0^1v`.
+    Prefix,
+}
+
+
+/// What happened at one house.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// The step of the first visit, where the start is step 0 and each move one more.
+    pub first: usize,
+    /// Presents delivered, by agent.
+    pub by_agent: Vec<usize>,
+}
+
+
+impl Record {
+    /// Presents delivered by anyone.
+    pub fn visits(&self) -> usize {
+        self.by_agent.iter().sum()
+    }
+
+    /// The agents that delivered at least one present, in order.
+    pub fn agents(&self) -> impl Iterator<Item = usize> + '_ {
+        self.by_agent.iter().enumerate().filter(|(_, &n)| n > 0).map(|(agent, _)| agent)
+    }
+}
+
+
+/// Who delivered presents where on lattice `L`, and when. Every agent delivers one
+/// present at the start. Each house keeps a [`Record`] in a `HashMap`, not a bit in a
+/// [`TiledSet`], so a walk costs dozens of bytes per house.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Walk<L: Lattice = Square> {
+    /// Where each agent is now.
+    positions: Vec<L::Point>,
+    records: HashMap<L::Point, Record>,
+    /// Houses in the order they were first visited.
+    order: Vec<L::Point>,
+    steps: usize,
+}
+
+
+impl<L: Lattice> Walk<L> {
+    fn new(agents: usize) -> Self {
+        let start = Record { first: 0, by_agent: vec![1; agents] };
+        let records = HashMap::from([(L::ORIGIN, start)]);
+        let (positions, order) = (vec![L::ORIGIN; agents], vec![L::ORIGIN]);
+        Walk { positions, records, order, steps: 0 }
+    }
+
+    fn visit(&mut self, agent: usize) {
+        self.steps += 1;
+        let (house, agents, step) = (self.positions[agent], self.agents(), self.steps);
+        let record = self.records.entry(house).or_insert_with(|| {
+            self.order.push(house);
+            Record { first: step, by_agent: vec![0; agents] }
+        });
+        record.by_agent[agent] += 1;
+    }
+
+    pub fn agents(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Where each agent is now.
+    pub fn positions(&self) -> &[L::Point] {
+        &self.positions
+    }
+
+    /// Moves made so far.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Houses that received at least one present.
+    pub fn houses(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn record(&self, house: L::Point) -> Option<&Record> {
+        self.records.get(&house)
+    }
+
+    /// Every visited house with its record, in the order they were first visited.
+    pub fn timeline(&self) -> impl Iterator<Item = (L::Point, &Record)> + '_ {
+        self.order.iter().map(|house| (*house, &self.records[house]))
+    }
+
+    /// Presents delivered to `house` by anyone.
+    pub fn visits(&self, house: L::Point) -> usize {
+        self.record(house).map_or(0, Record::visits)
+    }
+
+    /// Presents delivered to `house` by `agent`.
+    pub fn agent_visits(&self, house: L::Point, agent: usize) -> usize {
+        let record = self.record(house);
+        record.and_then(|record| record.by_agent.get(agent)).copied().unwrap_or(0)
+    }
+
+    /// Houses that received at least one present from `agent`.
+    pub fn agent_houses(&self, agent: usize) -> usize {
+        self.records.values().filter(|record| record.by_agent.get(agent) > Some(&0)).count()
+    }
+
+    /// Houses visited by more than one agent, in order.
+    pub fn shared(&self) -> Vec<L::Point> {
+        let mut shared: Vec<L::Point> = self.records.iter()
+            .filter(|(_, record)| record.agents().nth(1).is_some())
+            .map(|(&house, _)| house)
+            .collect();
+        shared.sort();
+        shared
+    }
+
+    /// Houses visited by every one of `agents`, in order; e.g. `&[0, 1]` for the houses
+    /// both Santa and Robo-Santa went to.
+    pub fn visited_by_all(&self, agents: &[usize]) -> Vec<L::Point> {
+        let mut houses: Vec<L::Point> = self.records.iter()
+            .filter(|(_, record)| agents.iter().all(|&a| record.by_agent.get(a) > Some(&0)))
+            .map(|(&house, _)| house)
+            .collect();
+        houses.sort();
+        houses
+    }
+
+    /// The step at which `n` different houses had been visited, if they ever were.
+    pub fn step_reaching(&self, n: usize) -> Option<usize> {
+        let house = self.order.get(n.checked_sub(1)?)?;
+        Some(self.records[house].first)
+    }
+
+    /// The house that got the most presents, and how many; ties go to the house
+    /// visited first.
+    pub fn most_visited(&self) -> (L::Point, usize) {
+        self.timeline()
+            .map(|(house, record)| (house, record.visits()))
+            .fold((L::ORIGIN, 0), |most, house| if house.1 > most.1 { house } else { most })
+    }
+}
+
+
+/// Walks `agents` agents through the directions in `input` on the square grid,
+/// handing out the moves as `assign` says.
+pub fn walk(input: &str, agents: usize, assign: Assign) -> Result<Walk> {
+    walk_in::<Square>(input, agents, assign)
+}
+
+
+/// [`walk`] on lattice `L`.
+pub fn walk_in<L: Lattice>(input: &str, agents: usize, assign: Assign) -> Result<Walk<L>> {
+    walk_with(input, agents, assign, |_| {})
+}
+
+
+/// [`walk_in`], calling `on_move` with the walk so far after every move.
+pub fn walk_with<L: Lattice>(
+    input: &str,
+    agents: usize,
+    assign: Assign,
+    mut on_move: impl FnMut(&Walk<L>),
+) -> Result<Walk<L>> {
+    if agents == 0 {
+        return Err(Error::semantic("at least one agent is needed"))
+    }
+    let moves = input.bytes().filter(|b| !matches!(b, b'\n' | b'\r')).count();
+    let (block, longer) = (moves / agents, moves % agents);
+    // The first `longer` blocks hold `block + 1` moves, the rest `block`.
+    let block_of = |i: usize| match i < longer * (block + 1) {
+        true => i / (block + 1),
+        false => longer + (i - longer * (block + 1)) / block,
+    };
+    let mut walk = Walk::new(agents);
+    let mut bytes = Bytes::new(input.as_bytes());
+    let mut prefix: Option<usize> = None;
+    let mut i = 0;
+    while let Some(byte) = bytes.next() {
+        let byte = byte?;
+        if matches!(byte, b'\n' | b'\r') {
+            continue
+        }
+        if assign == Assign::Prefix && byte.is_ascii_digit() {
+            let agent = prefix.unwrap_or(0)
+                .checked_mul(10)
+                .and_then(|n| n.checked_add((byte - b'0') as usize))
+                .filter(|&n| n < agents)
+                .ok_or_else(|| bytes.error(format!("there are only {agents} agents")))?;
+            prefix = Some(agent);
+            continue
+        }
+        let agent = match assign {
+            Assign::RoundRobin => i % agents,
+            Assign::Blocks => block_of(i),
+            Assign::Prefix => prefix.take()
+                .ok_or_else(|| bytes.error("expected an agent number before the move"))?,
+        };
+        step::<_, L>(&bytes, byte, &mut walk.positions[agent])?;
+        walk.visit(agent);
+        on_move(&walk);
+        i += 1;
+    }
+    if prefix.is_some() {
+        return Err(bytes.error("expected a move after the agent number"))
+    }
+    Ok(walk)
+}
+
+
+/// The lattices walks can take place on, for choosing one at run time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Geometry {
+    #[default]
+    Square,
+    Hex,
+    Cubic,
+}
+
+
+impl Geometry {
+    /// Houses that received at least one present; see [`walk_in`].
+    pub fn houses(self, input: &str, agents: usize, assign: Assign) -> Result<usize> {
+        match self {
+            Geometry::Square => Ok(walk_in::<Square>(input, agents, assign)?.houses()),
+            Geometry::Hex => Ok(walk_in::<Hex>(input, agents, assign)?.houses()),
+            Geometry::Cubic => Ok(walk_in::<Cubic>(input, agents, assign)?.houses()),
+        }
+    }
+}
+
+
+/// The smallest rectangle of houses holding every house in a walk, corners included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    /// The south-west corner.
+    pub min: House,
+    /// The north-east corner.
+    pub max: House,
+}
+
+
+impl Bounds {
+    pub fn width(&self) -> usize {
+        self.max.1.abs_diff(self.min.1) + 1
+    }
+
+    pub fn height(&self) -> usize {
+        self.max.0.abs_diff(self.min.0) + 1
+    }
+
+    /// Every house, row by row from the north-west corner.
+    fn houses(self) -> impl Iterator<Item = House> {
+        (self.min.0..=self.max.0)
+            .rev()
+            .flat_map(move |row| (self.min.1..=self.max.1).map(move |col| (row, col)))
+    }
+}
+
+
+/// Colours for the agents in a PPM image, reused in order when there are more agents.
+const PALETTE: [[u8; 3]; 8] = [
+    [255, 80, 80],
+    [80, 160, 255],
+    [80, 220, 80],
+    [255, 200, 40],
+    [200, 90, 255],
+    [40, 220, 220],
+    [255, 140, 0],
+    [255, 255, 255],
+];
+
+
+/// Draws walks. Houses that got more presents are drawn brighter, relative to the
+/// house that got the most.
+impl Walk {
+    pub fn bounds(&self) -> Bounds {
+        let houses = self.records.keys();
+        let (mut min, mut max) = ((0, 0), (0, 0));
+        for &(row, col) in houses {
+            min = (min.0.min(row), min.1.min(col));
+            max = (max.0.max(row), max.1.max(col));
+        }
+        Bounds { min, max }
+    }
+
+    /// One character per house within `bounds`, north at the top: `.` for none, the
+    /// number of presents up to 9, then `#`. With `agents`, where each agent is now is
+    /// marked with `@`.
+    pub fn to_ascii_within(&self, bounds: Bounds, agents: bool) -> String {
+        let mut ascii = String::with_capacity((bounds.width() + 1) * bounds.height());
+        for (i, house) in bounds.houses().enumerate() {
+            let c = match self.visits(house) {
+                _ if agents && self.positions.contains(&house) => '@',
+                0 => '.',
+                n @ 1..=9 => char::from(b'0' + n as u8),
+                _ => '#',
+            };
+            ascii.push(c);
+            if (i + 1).is_multiple_of(bounds.width()) {
+                ascii.push('\n');
+            }
+        }
+        ascii
+    }
+
+    /// The whole walk as text, for small walks; see [`Walk::to_ascii_within`].
+    pub fn to_ascii(&self) -> String {
+        self.to_ascii_within(self.bounds(), false)
+    }
+
+    fn pixels(&self) -> Result<usize> {
+        let bounds = self.bounds();
+        bounds.width()
+            .checked_mul(bounds.height())
+            .filter(|&n| n <= isize::MAX as usize / 3)
+            .ok_or_else(|| Error::semantic("the walk is too large to draw"))
+    }
+
+    /// How bright to draw a house with `visits` presents, out of 255.
+    fn brightness(&self, visits: usize, most: usize) -> u8 {
+        match visits {
+            0 => 0,
+            n => (64 + 191 * n / most) as u8,
+        }
+    }
+
+    /// A binary greyscale PGM image with one pixel per house, north at the top.
+    pub fn to_pgm(&self) -> Result<Vec<u8>> {
+        let bounds = self.bounds();
+        let mut image = format!("P5\n{} {}\n255\n", bounds.width(), bounds.height())
+            .into_bytes();
+        image.reserve(self.pixels()?);
+        let most = self.records.values().map(Record::visits).max().unwrap_or(1);
+        let pixel = |house| self.brightness(self.visits(house), most);
+        image.extend(bounds.houses().map(pixel));
+        Ok(image)
+    }
+
+    /// A binary colour PPM image with one pixel per house, north at the top. Each agent
+    /// has its own colour, and houses visited by several agents mix theirs.
+    pub fn to_ppm(&self) -> Result<Vec<u8>> {
+        let bounds = self.bounds();
+        let mut image = format!("P6\n{} {}\n255\n", bounds.width(), bounds.height())
+            .into_bytes();
+        image.reserve(self.pixels()? * 3);
+        let most = self.records.values().map(Record::visits).max().unwrap_or(1);
+        for house in bounds.houses() {
+            let Some(record) = self.record(house) else {
+                image.extend([0; 3]);
+                continue
+            };
+            let total = record.visits();
+            let brightness = self.brightness(total, most) as usize;
+            let mut mixed = [0_usize; 3];
+            for (agent, &n) in record.by_agent.iter().enumerate() {
+                let colour = PALETTE[agent % PALETTE.len()];
+                for (mixed, channel) in mixed.iter_mut().zip(colour) {
+                    *mixed += channel as usize * n;
+                }
+            }
+            image.extend(mixed.map(|mixed| (mixed * brightness / (total * 255)) as u8));
+        }
+        Ok(image)
+    }
+
+    /// Writes the walk to `path` as text, PGM or PPM, going by the extension: `txt`,
+    /// `pgm` or `ppm`.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let contents = match path.extension().and_then(|e| e.to_str()) {
+            Some("txt") => self.to_ascii().into_bytes(),
+            Some("pgm") => self.to_pgm()?,
+            Some("ppm") => self.to_ppm()?,
+            _ => {
+                let message = format!("can't tell how to draw {}", path.display());
+                return Err(Error::semantic(message))
+            },
+        };
+        Ok(std::fs::write(path, contents)?)
+    }
+}
+
+
+/// The walk as text after every `every` moves, and once more at the end, all drawn
+/// within the bounds of the whole walk and with each agent marked, to follow the
+/// directions move by move.
+pub fn frames(
+    input: &str,
+    agents: usize,
+    assign: Assign,
+    every: usize,
+) -> Result<Vec<String>> {
+    if every == 0 {
+        return Err(Error::semantic("frames must be at least one move apart"))
+    }
+    let bounds = walk(input, agents, assign)?.bounds();
+    let mut frames = Vec::new();
+    let mut moves = 0;
+    let last = walk_with(input, agents, assign, |walk: &Walk| {
+        moves += 1;
+        if moves % every == 0 {
+            frames.push(walk.to_ascii_within(bounds, true));
+        }
+    })?;
+    if moves % every != 0 || moves == 0 {
+        frames.push(last.to_ascii_within(bounds, true));
+    }
+    Ok(frames)
+}
+
+
+pub struct Day03;
+
+
+impl Solution for Day03 {
+    const DAY: u8 = 3;
+    const TITLE: &'static str = "Perfectly Spherical Houses in a Vacuum";
+
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.to_owned())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        part_1(input)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        part_2(input)
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use crate::input::load_day;
+
     #[test]
     fn part_1() {
-        let input = super::load_input().unwrap();
-        let output = super::part_1(&input);
+        let input = load_day(3).unwrap();
+        let output = super::part_1(&input).unwrap();
         assert_eq!(output, 2565);
     }
 
     #[test]
     fn part_2() {
-        let input = super::load_input().unwrap();
-        let output = super::part_2(&input);
+        let input = load_day(3).unwrap();
+        let output = super::part_2(&input).unwrap();
         assert_eq!(output, 2639);
     }
+
+    #[test]
+    fn stray_character() {
+        assert_eq!(super::part_1("^>v<\r\n").unwrap(), 4);
+        let e = super::part_2("^v\n^x").unwrap_err();
+        let expected = "parse error at line 2, column 2: unexpected character 'x'";
+        assert_eq!(e.to_string(), expected);
+    }
+
+    #[test]
+    fn reader() {
+        let input = std::io::BufReader::with_capacity(3, "^v^v^v^v^v".as_bytes());
+        assert_eq!(super::part_2_reader(input).unwrap(), 11);
+    }
+
+    #[test]
+    fn tokens() {
+        use crate::tokens::{Mode, TokenMap};
+
+        let input = load_day(3).unwrap();
+        let tokens = super::default_tokens();
+        let (output, _) = super::part_2_with(&input, &tokens, Mode::Strict).unwrap();
+        assert_eq!(output, 2639);
+        let tokens = TokenMap::new([("N", (1, 0)), ("S", (-1, 0))]).unwrap();
+        let (output, skipped) = super::part_1_with("N?NS", &tokens, Mode::Lenient).unwrap();
+        assert_eq!((output, skipped[&'?']), (3, 1));
+    }
+
+    #[test]
+    fn walk() {
+        use super::Assign;
+
+        let input = load_day(3).unwrap();
+        let output = super::walk(&input, 2, Assign::RoundRobin).unwrap();
+        assert_eq!(output.houses(), 2639);
+        let output = super::walk("^>v<", 1, Assign::default()).unwrap();
+        assert_eq!((output.houses(), output.visits((0, 0))), (4, 2));
+        let output = super::walk("^^>\nvv<", 2, Assign::Blocks).unwrap();
+        assert_eq!((output.agent_houses(0), output.agent_houses(1)), (4, 4));
+        assert_eq!(output.shared(), vec![(0, 0)]);
+        let output = super::walk("^^>>v", 4, Assign::Blocks).unwrap();
+        let houses: Vec<usize> = (0..4).map(|agent| output.agent_houses(agent)).collect();
+        assert_eq!(houses, [3, 2, 2, 2]);
+        let output = super::walk("0^1>10v0>", 11, Assign::Prefix).unwrap();
+        assert_eq!(output.agent_visits((1, 1), 0), 1);
+        assert_eq!(output.agent_visits((-1, 0), 10), 1);
+        assert_eq!(output.agent_houses(1), 2);
+        assert_eq!(output.shared(), vec![(0, 0)]);
+        let e = super::walk("0^2v", 2, Assign::Prefix).unwrap_err();
+        let expected = "parse error at line 1, column 3: there are only 2 agents";
+        assert_eq!(e.to_string(), expected);
+        assert!(super::walk("0^v", 2, Assign::Prefix).is_err());
+        assert!(super::walk("0^1", 2, Assign::Prefix).is_err());
+        assert!(super::walk("^", 0, Assign::RoundRobin).is_err());
+    }
+
+    #[test]
+    fn draw() {
+        use super::Assign;
+
+        let output = super::walk("^>v<^^", 1, Assign::RoundRobin).unwrap();
+        assert_eq!(output.to_ascii(), "1.\n21\n21\n");
+        let frames = super::frames("^>v<^^", 1, Assign::RoundRobin, 4).unwrap();
+        assert_eq!(frames, ["..\n11\n@1\n", "@.\n21\n21\n"]);
+        let pgm = output.to_pgm().unwrap();
+        assert_eq!(pgm, b"P5\n2 3\n255\n\x9f\0\xff\x9f\xff\x9f");
+        let output = super::walk("^v", 2, Assign::RoundRobin).unwrap();
+        let ppm = output.to_ppm().unwrap();
+        let header = b"P6\n1 3\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(&ppm[header.len()..][..6], [159, 49, 49, 167, 120, 167]);
+        let path = std::env::temp_dir().join("aoc-2015-day-03-draw.ppm");
+        output.save(&path).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), ppm);
+        std::fs::remove_file(&path).unwrap();
+        assert!(output.save(&path.with_extension("bmp")).is_err());
+        assert!(super::frames("^", 1, Assign::RoundRobin, 0).is_err());
+    }
+
+    #[test]
+    fn timeline() {
+        use super::Assign;
+
+        let output = super::walk("^vv^>", 2, Assign::RoundRobin).unwrap();
+        let timeline: Vec<_> = output.timeline().map(|(h, r)| (h, r.first)).collect();
+        assert_eq!(timeline, [((0, 0), 0), ((1, 0), 1), ((-1, 0), 2), ((0, 1), 5)]);
+        assert_eq!(output.visited_by_all(&[0, 1]), [(0, 0)]);
+        assert_eq!(output.visited_by_all(&[0]), [(0, 0), (0, 1), (1, 0)]);
+        assert_eq!((output.step_reaching(3), output.step_reaching(5)), (Some(2), None));
+        assert_eq!(output.step_reaching(0), None);
+        assert_eq!(output.most_visited(), ((0, 0), 4));
+        let record = output.record((-1, 0)).unwrap();
+        assert_eq!((record.visits(), record.agents().collect::<Vec<_>>()), (1, vec![1]));
+        assert_eq!(output.steps(), 5);
+    }
+
+    #[test]
+    fn geometries() {
+        use super::{Assign, Cubic, Geometry, Hex};
+
+        let input = load_day(3).unwrap();
+        let output = Geometry::default().houses(&input, 1, Assign::RoundRobin).unwrap();
+        assert_eq!(output, 2565);
+        let output = super::walk_in::<Hex>("wdsaqe", 1, Assign::RoundRobin).unwrap();
+        assert_eq!(output.positions(), [(0, 0)]);
+        assert_eq!(output.houses(), 6);
+        assert_eq!(output.visited_by_all(&[0])[..2], [(-1, 1), (0, -1)]);
+        assert_eq!(Geometry::Hex.houses("qd\nqd", 2, Assign::Blocks).unwrap(), 2);
+        let output = super::walk_in::<Cubic>("+^-v", 1, Assign::RoundRobin).unwrap();
+        assert_eq!((output.houses(), output.visits((0, 0, 0))), (4, 2));
+        assert_eq!(Geometry::Cubic.houses("0+1+", 2, Assign::Prefix).unwrap(), 2);
+        let e = Geometry::Hex.houses("w^", 1, Assign::RoundRobin).unwrap_err();
+        let expected = "parse error at line 1, column 2: unexpected character '^'";
+        assert_eq!(e.to_string(), expected);
+    }
+
+    #[test]
+    fn tiled_set() {
+        use std::collections::HashSet;
+
+        use super::TiledSet;
+
+        let mut set = TiledSet::new();
+        let mut expected = HashSet::new();
+        let mut house = (0_isize, 0_isize);
+        let input = load_day(3).unwrap();
+        for c in input.trim().chars() {
+            match c {
+                '>' => house.0 += 1,
+                '<' => house.0 -= 1,
+                '^' => house.1 += 1,
+                _ => house.1 -= 1,
+            }
+            assert_eq!(set.insert(house), expected.insert(house));
+        }
+        assert_eq!(set.len(), expected.len());
+        assert!(expected.iter().all(|&house| set.contains(house)));
+        assert!(!set.contains((-1_000, 1_000)) && !set.insert(house));
+        // The walk stays within 7 tiles, so under 2 bytes a house.
+        assert!(set.memory() < expected.len() * 2);
+        let mut sparse = TiledSet::new();
+        for i in 0..100 {
+            sparse.insert((64 * i, 0));
+        }
+        assert!(sparse.memory() >= sparse.len() * 512);
+        let visited = super::visit_reader(input.as_bytes(), 2).unwrap();
+        assert_eq!(visited.len(), 2639);
+    }
 }
@@ -19,32 +19,51 @@
 //! --- Part Two ---
 //! Now find one that starts with six zeroes.
 
-use std::{path::Path, thread::JoinHandle, sync::{Arc, atomic::AtomicIsize}};
+use std::{
+    collections::BTreeSet,
+    thread::JoinHandle,
+    sync::{
+        Arc, Mutex, OnceLock, PoisonError,
+        atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
 
 use crossbeam_channel::{Receiver, Sender};
-use md5;
 
+use crate::{error::{Error, Result}, solution::Solution};
 
-fn load_input() -> std::io::Result<String> {
-    let file_path = Path::new(file!())
-        .parent()
-        .expect("file not found")
-        .join("input.txt");
-    std::fs::read_to_string(file_path)
+
+/// How many numbers are tried between calls to a progress callback.
+pub const PROGRESS_EVERY: usize = 1 << 16;
+
+
+/// Whether the hash of `input` followed by `i` starts with `prefix`.
+fn mines(input: &str, i: usize, prefix: &str) -> bool {
+    let digest = md5::compute(format!("{input}{i}"));
+    format!("{digest:x}").starts_with(prefix)
+}
+
+
+/// The lowest number whose hash with `input` starts with five zeroes, searched on a
+/// single thread.
+pub fn part_1(input: &str) -> usize {
+    part_1_with(input, None)
 }
 
 
-fn part_1(input: &str) -> usize {
+/// [`part_1`], calling `progress` with how many numbers have been tried every
+/// [`PROGRESS_EVERY`] numbers.
+pub fn part_1_with(input: &str, progress: Option<&dyn Fn(usize)>) -> usize {
     let mut i: usize = 0;
-    println!("{}", input);
     loop {
-        let combined = format!("{input}{i}");
-        let digest = md5::compute(&combined);
-        let hex_str = format!("{:x}", digest);
-        if hex_str.starts_with("00000") {
+        if mines(input, i, "00000") {
             return i;
         }
         i += 1;
+        if let Some(progress) = progress.filter(|_| i.is_multiple_of(PROGRESS_EVERY)) {
+            progress(i);
+        }
     }
 }
 
@@ -77,7 +96,7 @@ fn check2(
         let combined = format!("{input}{i}");
         let digest = md5::compute(&combined);
         let hex_str = format!("{digest:x}");
-        if hex_str.starts_with(&prefix) {
+        if hex_str.starts_with(prefix) {
             solution.store(i as isize, std::sync::atomic::Ordering::Relaxed);
             break
         }
@@ -85,7 +104,12 @@ fn check2(
 }
 
 
-fn part_1_par(input: &str, n_threads: usize, prefix: &str) -> Result<usize, String> {
+/// Searches for a hash starting with `prefix` on `n_threads` workers. Whichever worker
+/// finds one first wins, which isn't always the lowest; see [`mine`] for that.
+pub fn part_1_par(input: &str, n_threads: usize, prefix: &str) -> Result<usize> {
+    if n_threads == 0 {
+        return Err(Error::semantic("at least one worker thread is required"))
+    }
     let (txi, rxi) = crossbeam_channel::bounded::<usize>(n_threads * 2);
     let (tx_soln, rx_soln) = crossbeam_channel::bounded::<usize>(0);
     let handles: Vec<JoinHandle<()>> = (0..n_threads).map(|_| {
@@ -103,18 +127,21 @@ fn part_1_par(input: &str, n_threads: usize, prefix: &str) -> Result<usize, Stri
             solution = soln;
             break
         }
-        txi.send(i).map_err(|e| e.to_string())?;
+        txi.send(i).map_err(|e| Error::Worker(e.to_string()))?;
         i += 1;
     }
     for handle in handles.into_iter() {
-        handle.join().map_err(|e| format!("{:?}", e))?
+        handle.join().map_err(|e| Error::Worker(format!("{:?}", e)))?
     }
     Ok(solution)
 }
 
 
 /// NOTE: just trying a different take on the solution compared to `part_1_par()`.
-fn part_1_par2(input: &str, n_threads: usize, prefix: &str) -> Result<usize, String> {
+pub fn part_1_par2(input: &str, n_threads: usize, prefix: &str) -> Result<usize> {
+    if n_threads == 0 {
+        return Err(Error::semantic("at least one worker thread is required"))
+    }
     let (txi, rxi) = crossbeam_channel::bounded::<usize>(n_threads * 2);
     let solution = Arc::new(AtomicIsize::new(-1));
     let handles: Vec<JoinHandle<()>> = (0..n_threads).map(|_| {
@@ -132,21 +159,266 @@ fn part_1_par2(input: &str, n_threads: usize, prefix: &str) -> Result<usize, Str
             drop(txi);
             break
         }
-        txi.send(i).map_err(|e| e.to_string())?;
+        txi.send(i).map_err(|e| Error::Worker(e.to_string()))?;
         i += 1;
     }
     for handle in handles.into_iter() {
-        handle.join().map_err(|e| format!("{:?}", e))?
+        handle.join().map_err(|e| Error::Worker(format!("{:?}", e)))?
     }
     Ok(solni as usize)
 }
 
 
+/// A flag for stopping a search from another thread; clones share the flag.
+#[derive(Clone, Debug, Default)]
+pub struct Cancel(Arc<AtomicBool>);
+
+
+impl Cancel {
+    pub fn new() -> Self {
+        Cancel::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+
+/// How many numbers a worker claims at a time by default: enough that claiming costs
+/// next to nothing, few enough that workers don't run far past the answer.
+pub const DEFAULT_BATCH: usize = 1024;
+
+
+/// How [`mine`] searches.
+pub struct Options<'a> {
+    /// Every core the machine has by default: with workers claiming whole ranges of
+    /// numbers they hardly ever contend, unlike the channel hand-off in [`part_1_par`],
+    /// which becomes unstable past 16 workers (see the notes in the tests below).
+    pub n_threads: usize,
+    /// How many numbers a worker claims at a time.
+    pub batch: usize,
+    pub cancel: Option<Cancel>,
+    /// How long to search for before giving up.
+    pub budget: Option<Duration>,
+    /// Called every [`PROGRESS_EVERY`] numbers with how many numbers, counting up from
+    /// 0, have all been tried.
+    pub progress: Option<&'a dyn Fn(usize)>,
+}
+
+
+impl Default for Options<'_> {
+    fn default() -> Self {
+        Options {
+            n_threads: available_parallelism(),
+            batch: DEFAULT_BATCH,
+            cancel: None,
+            budget: None,
+            progress: None,
+        }
+    }
+}
+
+
+impl Options<'_> {
+    /// The defaults, but with as many workers as [`tuned_n_threads`] finds worth it.
+    pub fn tuned(input: &str) -> Self {
+        Options { n_threads: tuned_n_threads(input), ..Options::default() }
+    }
+}
+
+
+/// [`mine`], along with how many numbers, counting up from 0, have all been tried.
+fn search(input: &str, prefix: &str, options: &Options) -> Result<(Option<usize>, usize)> {
+    if options.n_threads == 0 {
+        return Err(Error::semantic("at least one worker thread is required"))
+    }
+    if options.batch == 0 {
+        return Err(Error::semantic("workers must claim at least one number at a time"))
+    }
+    let (batch, deadline) = (options.batch, options.budget.map(|b| Instant::now() + b));
+    let next = AtomicUsize::new(0);
+    let lowest = AtomicUsize::new(usize::MAX);
+    let stop = AtomicBool::new(false);
+    // The ranges finished out of order, and how far every number has been tried.
+    let finished = Mutex::new((BTreeSet::new(), 0));
+    let tried = AtomicUsize::new(0);
+    let finish = |start: usize| {
+        let mut finished = finished.lock().unwrap_or_else(PoisonError::into_inner);
+        let (ranges, upto) = &mut *finished;
+        ranges.insert(start);
+        while ranges.remove(upto) {
+            *upto += batch;
+        }
+        // Numbers past the lowest found so far were skipped, not tried.
+        let upto = (*upto).min(lowest.load(Ordering::Relaxed).saturating_add(1));
+        tried.fetch_max(upto, Ordering::Relaxed);
+    };
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..options.n_threads).map(|_| scope.spawn(|| {
+            while !stop.load(Ordering::Relaxed) {
+                let start = next.fetch_add(batch, Ordering::Relaxed);
+                let done = (start..start.saturating_add(batch)).any(|i| {
+                    if i < lowest.load(Ordering::Relaxed) && mines(input, i, prefix) {
+                        lowest.fetch_min(i, Ordering::Relaxed);
+                    }
+                    i >= lowest.load(Ordering::Relaxed)
+                });
+                finish(start);
+                if done {
+                    return
+                }
+            }
+        })).collect();
+        let mut reported = 0;
+        let mut report = |tried: usize| {
+            let Some(progress) = options.progress else {
+                return
+            };
+            while reported + PROGRESS_EVERY <= tried {
+                reported += PROGRESS_EVERY;
+                progress(reported);
+            }
+        };
+        while handles.iter().any(|handle| !handle.is_finished()) {
+            let cancelled = options.cancel.as_ref().is_some_and(Cancel::is_cancelled);
+            if cancelled || deadline.is_some_and(|d| Instant::now() > d) {
+                stop.store(true, Ordering::Relaxed);
+            }
+            report(tried.load(Ordering::Relaxed));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        for handle in handles {
+            handle.join().map_err(|e| Error::Worker(format!("{e:?}")))?;
+        }
+        let lowest = Some(lowest.load(Ordering::Relaxed)).filter(|&i| i != usize::MAX);
+        let tried = tried.load(Ordering::Relaxed);
+        report(tried);
+        Ok((lowest, tried))
+    })
+}
+
+
+/// The lowest number whose hash with `input` starts with `prefix`, searched on
+/// `options.n_threads` workers, or `None` if the search was cancelled or ran out of
+/// time first.
+///
+/// Workers claim ranges of `options.batch` numbers in order from a shared counter, and
+/// always finish a range they've claimed, up to the lowest number found so far. Every
+/// number below the answer has then been tried, so it is the lowest whichever worker
+/// finds it.
+pub fn mine(input: &str, prefix: &str, options: &Options) -> Result<Option<usize>> {
+    Ok(search(input, prefix, options)?.0)
+}
+
+
+/// How fast [`mine`] runs on some number of workers.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scaling {
+    pub n_threads: usize,
+    /// Numbers tried per second.
+    pub rate: f64,
+    /// The rate relative to that on one worker.
+    pub speedup: f64,
+}
+
+
+/// Times [`mine`] on `input` for `sample` on each of 1, 2, 4 and so on workers up to
+/// `max_threads`, with `max_threads` itself last, claiming `batch` numbers at a time.
+/// Nothing is ever found, so every run lasts the whole sample.
+pub fn scaling(
+    input: &str,
+    batch: usize,
+    max_threads: usize,
+    sample: Duration,
+) -> Result<Vec<Scaling>> {
+    let mut counts: Vec<usize> = std::iter::successors(Some(1_usize), |n| n.checked_mul(2))
+        .take_while(|&n| n < max_threads)
+        .collect();
+    counts.push(max_threads.max(1));
+    let mut results: Vec<Scaling> = Vec::with_capacity(counts.len());
+    for n_threads in counts {
+        let budget = Some(sample);
+        let options = Options { n_threads, batch, budget, ..Options::default() };
+        let start = Instant::now();
+        // No hash starts with a `g`, as hashes are written in hexadecimal.
+        let (_, tried) = search(input, "g", &options)?;
+        let rate = tried as f64 / start.elapsed().as_secs_f64();
+        let speedup = results.first().map_or(1.0, |one| rate / one.rate);
+        results.push(Scaling { n_threads, rate, speedup });
+    }
+    Ok(results)
+}
+
+
+/// The fewest workers among `results` from [`scaling`] that get within 5% of the best
+/// rate, or 1 if there are none.
+pub fn tuned(results: &[Scaling]) -> usize {
+    let best = results.iter().map(|result| result.rate).fold(0.0, f64::max);
+    let tuned = results.iter().find(|result| result.rate >= best * 0.95);
+    tuned.map_or(1, |result| result.n_threads)
+}
+
+
+/// How long [`tuned_n_threads`] times each thread count for.
+const PROBE_SAMPLE: Duration = Duration::from_millis(20);
+
+
+/// The number of workers [`tuned`] picks from a short [`scaling`] probe on `input`, up
+/// to the machine's parallelism. The probe only runs the first time; every later call
+/// gets the same count, whatever the input.
+pub fn tuned_n_threads(input: &str) -> usize {
+    static TUNED: OnceLock<usize> = OnceLock::new();
+    *TUNED.get_or_init(|| {
+        let probe = scaling(input, DEFAULT_BATCH, available_parallelism(), PROBE_SAMPLE);
+        probe.map_or_else(|_| available_parallelism(), |results| tuned(&results))
+    })
+}
+
+
+fn available_parallelism() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+
+pub struct Day04;
+
+
+impl Solution for Day04 {
+    const DAY: u8 = 4;
+    const TITLE: &'static str = "The Ideal Stocking Stuffer";
+
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.trim().to_owned())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        Ok(part_1(input))
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        let options = Options::tuned(input);
+        mine(input, "000000", &options)?
+            .ok_or_else(|| Error::Worker(String::from("the search stopped early")))
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
+    use crate::input::load_day;
+
     #[test]
     fn part_1() {
-        let input = super::load_input().unwrap();
+        let input = load_day(4).unwrap();
         let output = super::part_1(input.trim());
         assert_eq!(output, 254575);
     }
@@ -156,7 +428,7 @@ mod tests {
         // NOTE: time to run becomes unstable above 16 threads, even when more are
         // available.
         let n_threads = 8;
-        let input = super::load_input().unwrap();
+        let input = load_day(4).unwrap();
         let output = super::part_1_par(input.trim(), n_threads, "00000").unwrap();
         assert_eq!(output, 254575);
     }
@@ -166,7 +438,7 @@ mod tests {
         // NOTE: time to run becomes unstable above 16 threads, even when more are
         // available.
         let n_threads = 8;
-        let input = super::load_input().unwrap();
+        let input = load_day(4).unwrap();
         let output = super::part_1_par2(input.trim(), n_threads, "00000").unwrap();
         assert_eq!(output, 254575);
     }
@@ -176,9 +448,82 @@ mod tests {
         // NOTE: time to run becomes unstable above 16 threads, even when more are
         // available.
         let n_threads = 8;
-        let input = super::load_input().unwrap();
+        let input = load_day(4).unwrap();
         let output = super::part_1_par(input.trim(), n_threads, "000000").unwrap();
         assert_eq!(output, 1038736);
 
     }
+
+    #[test]
+    fn mine() {
+        use std::{cell::Cell, time::Duration};
+
+        use super::{Cancel, Options, PROGRESS_EVERY};
+
+        for n_threads in [1, 3, 8] {
+            let options = Options { n_threads, ..Options::default() };
+            assert_eq!(super::mine("abc", "0", &options).unwrap(), Some(5));
+            assert_eq!(super::mine("abc", "00", &options).unwrap(), Some(18));
+            assert_eq!(super::mine("abc", "000", &options).unwrap(), Some(2196));
+        }
+        let calls = Cell::new(0);
+        let progress = |checked: usize| {
+            assert_eq!(checked, (calls.get() + 1) * PROGRESS_EVERY);
+            calls.set(calls.get() + 1);
+        };
+        let options =
+            Options { n_threads: 8, progress: Some(&progress), ..Options::default() };
+        let input = load_day(4).unwrap();
+        assert_eq!(super::mine(input.trim(), "00000", &options).unwrap(), Some(254575));
+        assert_eq!(calls.get(), 254575 / PROGRESS_EVERY);
+        let cancel = Cancel::new();
+        cancel.cancel();
+        let options = Options { cancel: Some(cancel), ..Options::default() };
+        assert_eq!(super::mine("abc", "0000000000", &options).unwrap(), None);
+        let budget = Some(Duration::from_millis(50));
+        let options = Options { budget, ..Options::default() };
+        assert_eq!(super::mine("abc", "0000000000", &options).unwrap(), None);
+        let options = Options { n_threads: 0, ..Options::default() };
+        assert!(super::mine("abc", "0", &options).is_err());
+    }
+
+    #[test]
+    fn batches() {
+        use std::time::Duration;
+
+        use super::{Options, Scaling};
+
+        for batch in [1, 7, 4096] {
+            let options = Options { n_threads: 4, batch, ..Options::default() };
+            assert_eq!(super::mine("abc", "000", &options).unwrap(), Some(2196));
+        }
+        let options = Options { batch: 0, ..Options::default() };
+        assert!(super::mine("abc", "0", &options).is_err());
+        let output = super::scaling("abc", 64, 3, Duration::from_millis(20)).unwrap();
+        let counts: Vec<usize> = output.iter().map(|s| s.n_threads).collect();
+        assert_eq!(counts, [1, 2, 3]);
+        assert!(output.iter().all(|s| s.rate > 0.0) && output[0].speedup == 1.0);
+        let at = |n_threads, rate| Scaling { n_threads, rate, speedup: 1.0 };
+        assert_eq!(super::tuned(&[at(1, 1.0), at(2, 1.95), at(4, 1.98), at(8, 2.0)]), 2);
+        assert_eq!(super::tuned(&[at(1, 1.0), at(2, 0.5)]), 1);
+        assert_eq!(super::tuned(&[]), 1);
+        let n_threads = Options::tuned("abc").n_threads;
+        assert!((1..=super::available_parallelism()).contains(&n_threads));
+    }
+
+    /// Meant for an otherwise idle many-core machine, in release:
+    /// `cargo test --release -- --ignored --test-threads 1 near_linear`. Only thread
+    /// counts up to half the logical cores are checked, so hyperthreads don't count.
+    #[test]
+    #[ignore = "needs an otherwise idle many-core machine"]
+    fn near_linear() {
+        use std::time::Duration;
+
+        let cores = super::available_parallelism();
+        let sample = Duration::from_millis(500);
+        let output = super::scaling("abc", super::DEFAULT_BATCH, cores, sample).unwrap();
+        for result in output.iter().filter(|result| 2 * result.n_threads <= cores) {
+            assert!(result.speedup >= 0.75 * result.n_threads as f64, "{result:?}");
+        }
+    }
 }
@@ -18,6 +18,8 @@
 //!
 //! How many strings are nice?
 
+use crate::{error::Result, solution::Solution};
+
 // ==== Part One
 
 fn three_vowels(s: &str) -> bool {
@@ -60,12 +62,13 @@ fn no_naughty_substr(s: &str) -> bool {
     true
 }
 
-fn part_1(input: &[String]) -> usize {
+/// How many strings are nice under the first set of rules.
+pub fn part_1(input: &[String]) -> usize {
     let n_nice = input.iter()
         .fold(0_usize, |mut n, s| {
-            if three_vowels(&s)
-                && serial_letter(&s)
-                && no_naughty_substr(&s)
+            if three_vowels(s)
+                && serial_letter(s)
+                && no_naughty_substr(s)
             {
                 n += 1;
             }
@@ -85,16 +88,14 @@ fn has_any_pair_twice(s: &str) -> bool {
     while let Some(c) = chars.next() {
         if let Some(peek) = chars.peek() {
             let pair = [c, *peek];
-            if pairs.contains(&pair) {
-                if pairs.last().unwrap() != &pair {
-                    // this prevents counting something like "aaa"
-                    return true
-                }
+            // the `last()` check prevents counting something like "aaa"
+            if pairs.contains(&pair) && pairs.last().unwrap() != &pair {
+                return true
             }
             pairs.push(pair);
         }
     }
-    return false
+    false
 }
 
 
@@ -108,7 +109,7 @@ fn has_sandwhich(s: &str) -> bool {
         chars.next().unwrap()
     ];
     while let Some(c) = chars.next() {
-        if &c == &current_pair[0] || chars.peek() == Some(&current_pair[1]) {
+        if c == current_pair[0] || chars.peek() == Some(&current_pair[1]) {
             return true
         }
         current_pair.swap(0, 1);
@@ -118,10 +119,11 @@ fn has_sandwhich(s: &str) -> bool {
 }
 
 
-fn part_2(input: &[String]) -> usize {
+/// How many strings are nice under the second set of rules.
+pub fn part_2(input: &[String]) -> usize {
     let n_nice = input.iter()
         .fold(0_usize, |n, s| {
-            if has_any_pair_twice(&s) && has_sandwhich(&s) {
+            if has_any_pair_twice(s) && has_sandwhich(s) {
                 n + 1
             } else {
                 n
@@ -131,34 +133,46 @@ fn part_2(input: &[String]) -> usize {
 }
 
 
+pub struct Day05;
+
+
+impl Solution for Day05 {
+    const DAY: u8 = 5;
+    const TITLE: &'static str = "Doesn't He Have Intern-Elves For This?";
+
+    type Input = Vec<String>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        Ok(part_1(input))
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        Ok(part_2(input))
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
-    use std::{path::Path, fs::File, io::{BufRead, BufReader}};
-
-    fn load_input() -> std::io::Result<Vec<String>> {
-        let file_path = Path::new(file!())
-            .parent()
-            .expect("impossible, every file has a parent")
-            .join("input.txt");
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let out = reader.lines()
-            // just have a panic if line can't be read -- it's only for testing
-            .map(|line| line.unwrap())
-            .collect();
-        Ok(out)
-    }
+    use super::Day05;
+    use crate::{input::load_day, solution::Solution};
 
     #[test]
     fn part_1() {
-        let input = load_input().unwrap();
+        let input = Day05::parse(&load_day(5).unwrap()).unwrap();
         let output = super::part_1(&input);
         assert_eq!(output, 258);
     }
 
     #[test]
     fn part_2() {
-        let input = load_input().unwrap();
+        let input = Day05::parse(&load_day(5).unwrap()).unwrap();
         let output = super::part_2(&input);
         assert_eq!(output, 53);
     }
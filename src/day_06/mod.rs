@@ -61,78 +61,99 @@
 //!    `flat_idx_ranges()` function.
 
 
-use std::num::ParseIntError;
+use crate::{error::{Error, Result}, solution::Solution};
 
 #[derive(Clone, Copy, Debug)]
-enum Action {
+pub enum Action {
     Off,
     On,
     Toggle,
 }
 
 
-impl From<&str> for Action {
-    fn from(value: &str) -> Self {
+impl TryFrom<&str> for Action {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
         if value.starts_with("toggle") {
-            return Action::Toggle
+            return Ok(Action::Toggle)
         } else if value.starts_with("turn on") {
-            return Action::On
+            return Ok(Action::On)
         } else if value.starts_with("turn off") {
-            return Action::Off
+            return Ok(Action::Off)
         }
-        unreachable!("we shouldn't be here")
+        Err(Error::parse(1, 1, format!("unknown action: {value}")))
     }
 }
 
 
-fn single_coord_from_str(s: &str) -> Result<[usize; 2], String> {
-    let v = s.split(",")
+/// `column` is where `s` starts within its line, for error reporting.
+fn single_coord_from_str(s: &str, column: usize) -> Result<[usize; 2]> {
+    let v = s.split(',')
         .map(|x| x.parse::<usize>())
-        .collect::<Result<Vec<usize>, ParseIntError>>()
-        .map_err(|e| e.to_string())?;
+        .collect::<std::result::Result<Vec<usize>, _>>()
+        .map_err(|e| Error::parse(1, column, format!("{e}: {s}")))?;
     if v.len() != 2 {
-        return Err(format!("malformed string: {}", s))
+        return Err(Error::parse(1, column, format!("malformed coordinate: {s}")))
     }
     Ok([v[0], v[1]])
 }
 
 
-fn coords_from_str(s: &str) -> Result<[[usize; 2]; 2], String> {
+fn coords_from_str(s: &str) -> Result<[[usize; 2]; 2]> {
     let splits = s.split(" through ").collect::<Vec<&str>>();
     if splits.len() != 2 {
-        return Err(format!("malformed string:  {}", s))
+        return Err(Error::parse(1, 1, format!("malformed string:  {}", s)))
     }
     let (s1, s2) = (
         splits[0].split_whitespace().last(),
         splits[1].split_whitespace().last()
     );
-    if s1.is_none() || s2.is_none() {
-        return Err(format!("malformed string:  {}", s))
-    }
-    let c1 = single_coord_from_str(s1.unwrap())?;
-    let c2 = single_coord_from_str(s2.unwrap())?;
+    let (Some(s1), Some(s2)) = (s1, s2) else {
+        return Err(Error::parse(1, 1, format!("malformed string:  {}", s)))
+    };
+    // both are subslices of `s`, so their offsets give the columns
+    let column = |x: &str| x.as_ptr() as usize - s.as_ptr() as usize + 1;
+    let c1 = single_coord_from_str(s1, column(s1))?;
+    let c2 = single_coord_from_str(s2, column(s2))?;
     Ok([c1, c2])
 }
 
 
 #[derive(Debug)]
-struct Instruction {
+pub struct Instruction {
     pub action: Action,
     pub coords: [[usize; 2]; 2],
 }
 
 
 impl TryFrom<String> for Instruction {
-    type Error = String;
+    type Error = Error;
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        let action: Action = value.as_str().into();
+    fn try_from(value: String) -> Result<Self> {
+        let action = Action::try_from(value.as_str())?;
         let coords = coords_from_str(&value)?;
         Ok(Self { action, coords })
     }
 }
 
 
+/// Rejects rectangles whose corners are out of order or fall outside the grid, either of
+/// which would otherwise panic while indexing.
+fn check_coords(coords: &[[usize; 2]; 2], n_rows: usize, n_cols: usize) -> Result<()> {
+    let [[r1, c1], [r2, c2]] = *coords;
+    if r1 > r2 || c1 > c2 {
+        return Err(Error::semantic(format!("corners out of order: {coords:?}")))
+    }
+    if r2 >= n_rows || c2 >= n_cols {
+        return Err(Error::semantic(format!(
+            "{coords:?} falls outside the {n_rows}x{n_cols} grid"
+        )))
+    }
+    Ok(())
+}
+
+
 /// `coord` should be [row, col], and 0-based indexing.
 fn flat_idx(coord: &[usize; 2], n_cols: usize) -> usize {
     coord[0] * n_cols + coord[1]
@@ -173,30 +194,32 @@ fn bit_idx(idx: usize) -> usize {
 }
 
 
-fn update_bit(bitset: &mut [u8], idx: usize, op: i8) -> Result<(), String> {
+fn update_bit(bitset: &mut [u8], idx: usize, op: i8) -> Result<()> {
     let byte_i = byte_idx(idx);
     let pos = bit_idx(idx);
     match op {
         0 => {
-            bitset[byte_i] = bitset[byte_i] & !(1u8 << pos);
+            bitset[byte_i] &= !(1u8 << pos);
         },
         1 => {
-            bitset[byte_i] = bitset[byte_i] | (1u8 << pos);
+            bitset[byte_i] |= 1u8 << pos;
         },
         -1 => {
-            bitset[byte_i] = bitset[byte_i] ^ (1u8 << pos);
+            bitset[byte_i] ^= 1u8 << pos;
         },
-        _ => return Err(format!("invalid op: {}", op))
+        _ => return Err(Error::semantic(format!("invalid op: {}", op)))
     }
     Ok(())
 }
 
 
-fn part_1(input: &[Instruction]) -> Result<usize, String> {
+/// How many lights of the 1000x1000 grid are lit after following `input`.
+pub fn part_1(input: &[Instruction]) -> Result<usize> {
     const N_BYTES: usize = 1_000_000 / 8;
     let total_cols = 1000;
     let mut bitset = [0u8; N_BYTES];
     for instruct in input {
+        check_coords(&instruct.coords, N_BYTES * 8 / total_cols, total_cols)?;
         let ranges = flat_idx_ranges(&instruct.coords, total_cols);
         for range in ranges {
             for idx in range {
@@ -216,15 +239,20 @@ fn part_1(input: &[Instruction]) -> Result<usize, String> {
 }
 
 
-fn part_2(input: &[Instruction], lights: &mut [usize], n_cols: usize) -> usize {
+/// The total brightness of `lights`, a row-major grid `n_cols` wide, after following
+/// `input`.
+pub fn part_2(input: &[Instruction], lights: &mut [usize], n_cols: usize) -> Result<usize> {
+    if n_cols == 0 {
+        return Err(Error::semantic("the grid must be at least one column wide"))
+    }
     for instruct in input {
+        check_coords(&instruct.coords, lights.len() / n_cols, n_cols)?;
         let ranges = flat_idx_ranges(&instruct.coords, n_cols);
         for range in ranges {
             for idx in range {
                 match instruct.action {
-                    Action::Off => match lights[idx] > 0 {
-                        true => lights[idx] -= 1,
-                        false => {},
+                    Action::Off => if lights[idx] > 0 {
+                        lights[idx] -= 1
                     },
                     Action::On => lights[idx] += 1,
                     Action::Toggle => lights[idx] += 2,
@@ -232,36 +260,48 @@ fn part_2(input: &[Instruction], lights: &mut [usize], n_cols: usize) -> usize {
             }
         }
     }
-    lights.iter().sum()
+    Ok(lights.iter().sum())
 }
 
 
-#[cfg(test)]
-mod tests {
-    use std::{path::Path, fs::File, io::{BufRead, BufReader}};
-
-    use super::Instruction;
-
-    fn load_input(fname: &str) -> std::io::Result<Vec<Instruction>> {
-        let file_path = Path::new(file!())
-            .parent()
-            .expect("every file has a parent")
-            .join(fname);
-        let file = File::open(file_path)?;
-        let reader = BufReader::new(file);
-        let data = reader.lines()
-            .filter_map(|l| {
-                let s = l.unwrap();
-                if s.is_empty() {
-                    return None
-                }
-                let instruct = s.try_into().expect("invalid data format");
-                Some(instruct)
+pub struct Day06;
+
+
+impl Solution for Day06 {
+    const DAY: u8 = 6;
+    const TITLE: &'static str = "Probably a Fire Hazard";
+
+    type Input = Vec<Instruction>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, l)| !l.trim().is_empty())
+            .map(|(i, l)| {
+                Instruction::try_from(l.trim_end().to_owned()).map_err(|e| e.on_line(i + 1))
             })
-            .collect();
-        Ok(data)
+            .collect()
     }
 
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        part_1(input)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        let mut lights = vec![0_usize; 1_000_000];
+        part_2(input, &mut lights, 1000)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Day06;
+    use crate::{input::load_day, solution::Solution};
+
     #[test]
     fn flat_idx() {
         let cases: Vec<([usize; 2], [usize; 2], usize)> = vec![
@@ -282,6 +322,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::type_complexity)]
     fn flat_idx_ranges() {
         let cases: Vec<([usize; 2], [[usize; 2]; 2], Vec<Vec<usize>>)> = vec![
             // ([n_rows, n_cols], [[r, c], [r, c]], expected)
@@ -309,7 +350,7 @@ mod tests {
     #[test]
     fn update_bit_toggle() {
         const N_BYTES: usize = 16 / 8;
-        let mut bitset = [0u8, 255];
+        let mut bitset: [u8; N_BYTES] = [0u8, 255];
         super::update_bit(&mut bitset, 1, -1).unwrap();
         super::update_bit(&mut bitset, 11, -1).unwrap();
         assert_eq!(&format!("{:08b}", bitset[0]), "01000000");
@@ -318,17 +359,37 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let input = load_input("input.txt").unwrap();
+        let input = Day06::parse(&load_day(6).unwrap()).unwrap();
         let count = super::part_1(&input).unwrap();
         assert_eq!(count, 400410);
     }
 
     #[test]
     fn part_2() {
-        let input = load_input("input.txt").unwrap();
+        let input = Day06::parse(&load_day(6).unwrap()).unwrap();
         let mut lights = vec![0_usize; 1_000_000];
         let n_cols = 1000;
-        let output = super::part_2(&input, &mut lights, n_cols);
+        let output = super::part_2(&input, &mut lights, n_cols).unwrap();
         assert_eq!(output, 15343601);
     }
+
+    #[test]
+    fn malformed() {
+        let input = "toggle 0,0 through 1,1\nturn sideways 0,0 through 1,1";
+        let e = Day06::parse(input).unwrap_err();
+        let expected = "line 2, column 1: unknown action: turn sideways 0,0 through 1,1";
+        assert!(e.to_string().ends_with(expected));
+        let e = Day06::parse("turn on 0,0 through 1,x").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 1, column 21:"));
+    }
+
+    #[test]
+    fn out_of_grid() {
+        let input = Day06::parse("turn on 0,0 through 1000,1").unwrap();
+        assert!(super::part_1(&input).is_err());
+        let input = Day06::parse("turn on 5,5 through 1,1").unwrap();
+        assert!(Day06::part_two(&input).is_err());
+        let input = Day06::parse("turn on 0,0 through 0,0").unwrap();
+        assert!(super::part_2(&input, &mut [0; 4], 0).is_err());
+    }
 }
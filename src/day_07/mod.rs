@@ -29,7 +29,7 @@
 //! 
 //! For example, here is a simple circuit:
 //! 
-//! ```
+//! ```text
 //! 123 -> x
 //! 456 -> y
 //! x AND y -> d
@@ -41,7 +41,7 @@
 //! ```
 //! After it is run, these are the signals on the wires:
 //! 
-//! ```
+//! ```text
 //! d: 72
 //! e: 507
 //! f: 492
@@ -59,10 +59,12 @@
 //! the other wires (including wire `a`). What new signal is ultimately provided to wire
 //! `a`?
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+use crate::{error::{Error, Result}, solution::Solution};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum Gate {
+pub enum Gate {
     And,
     Or,
     Not,
@@ -72,7 +74,7 @@ enum Gate {
 
 
 #[derive(Debug, PartialEq)]
-enum Input {
+pub enum Input {
     Op(Gate),
     Signal(u16),
     Wire(String),
@@ -80,115 +82,136 @@ enum Input {
 
 
 #[derive(Debug)]
-struct Instruction {
-    inputs: Vec<Input>,
-    wire: String,
+pub struct Instruction {
+    pub inputs: Vec<Input>,
+    pub wire: String,
+}
+
+
+/// Checks that `inputs` is one of `x`, `NOT x` or `x GATE y`, where the operands are
+/// signals or wires.
+fn check_shape(inputs: &[Input]) -> bool {
+    let is_operand = |x: &Input| !matches!(x, Input::Op(_));
+    match inputs {
+        [x] => is_operand(x),
+        [Input::Op(Gate::Not), x] => is_operand(x),
+        [x, Input::Op(g), y] => *g != Gate::Not && is_operand(x) && is_operand(y),
+        _ => false,
+    }
 }
 
 
 impl TryFrom<&str> for Instruction {
-    type Error = String;
+    type Error = Error;
 
-    fn try_from(input: &str) -> Result<Self, String> {
+    fn try_from(input: &str) -> Result<Self> {
         let parts: Vec<String> = input.split(" -> ")
             .map(|x| x.trim().to_string())
             .collect();
-        if parts.len() != 2 { return Err(format!("malformed input: {input}")) }
+        if parts.len() != 2 {
+            return Err(Error::parse(1, 1, format!("malformed input: {input}")))
+        }
         let (lhs, rhs) = (&parts[0], &parts[1]);
-        let gates = vec!["AND", "OR", "NOT", "LSHIFT", "RSHIFT"];
+        if rhs.is_empty() || rhs.contains(char::is_whitespace) {
+            let column = input.find(" -> ").unwrap_or(0) + 5;
+            return Err(Error::parse(1, column, format!("invalid target wire: {rhs:?}")))
+        }
+        let gates = ["AND", "OR", "NOT", "LSHIFT", "RSHIFT"];
         let inputs = lhs
             .split_whitespace()
             .map(|x| {
                 if gates.contains(&x) {
                     match x {
-                        "AND" => Ok(Input::Op(Gate::And)),
-                        "OR" => Ok(Input::Op(Gate::Or)),
-                        "NOT" => Ok(Input::Op(Gate::Not)),
-                        "LSHIFT" => Ok(Input::Op(Gate::Lshift)),
-                        "RSHIFT" => Ok(Input::Op(Gate::Rshift)),
-                        _ => Err(String::from("invalid op")),
+                        "AND" => Input::Op(Gate::And),
+                        "OR" => Input::Op(Gate::Or),
+                        "NOT" => Input::Op(Gate::Not),
+                        "LSHIFT" => Input::Op(Gate::Lshift),
+                        _ => Input::Op(Gate::Rshift),
                     }
                 } else {
                     let value = x.parse::<u16>();
                     match value {
-                        Ok(signal) => Ok(Input::Signal(signal)),
-                        _ => Ok(Input::Wire(x.to_string())),
+                        Ok(signal) => Input::Signal(signal),
+                        _ => Input::Wire(x.to_string()),
                     }
                 }
             })
-            .collect::<Result<Vec<Input>, _>>()?;
+            .collect::<Vec<Input>>();
+        if !check_shape(&inputs) {
+            return Err(Error::parse(1, 1, format!("malformed gate expression: {lhs}")))
+        }
         Ok(Self { inputs, wire: rhs.to_owned() })
     }
 }
 
 
-fn try_parse_instructions(input: String) -> Result<Vec<Instruction>, String> {
-    let lines = input.split("\n");
+pub fn try_parse_instructions(input: String) -> Result<Vec<Instruction>> {
+    let lines = input.split('\n');
     let instructions = lines
-        .filter_map(|l| {
+        .enumerate()
+        .filter_map(|(i, l)| {
+            let l = l.trim_end();
             if l.is_empty() { return None }
-            Some(l.try_into())
+            Some(Instruction::try_from(l).map_err(|e| e.on_line(i + 1)))
         })
-        .collect::<Result<Vec<Instruction>, _>>()?;
+        .collect::<Result<Vec<Instruction>>>()?;
     Ok(instructions)
 }
 
 
-fn signal_from_processed_inputs(inputs: &[Input]) -> u16 {
-    match inputs[0] {
-        Input::Signal(x) => if inputs.len() == 1 {
-            return x
-        }
-        Input::Op(Gate::Not) => match inputs[1] {
-            Input::Signal(x) => return !x,
-            _ => unreachable!("not gate must be followed by a signal")
-        },
-        _ => {}
-    }
-    // all other cases should contain pattern `signal gate signal`
-    // even though a signal can be an amount by which to shift
-    let a = match inputs[0] {
-        Input::Signal(a) => a,
-        _ => unreachable!()
-    };
-    let b = match inputs[2] {
-        Input::Signal(b) => b,
-        _ => unreachable!()
-    };
-    match inputs[1] {
-        Input::Op(g) => match g {
+fn signal_from_processed_inputs(inputs: &[Input]) -> Result<u16> {
+    // all cases other than a plain signal or `NOT signal` should contain the pattern
+    // `signal gate signal`, even though a signal can be an amount by which to shift
+    let signal = match inputs {
+        [Input::Signal(x)] => *x,
+        [Input::Op(Gate::Not), Input::Signal(x)] => !x,
+        [Input::Signal(a), Input::Op(g), Input::Signal(b)] => match g {
             Gate::And => a & b,
             Gate::Or => a | b,
-            Gate::Lshift => a << b,
-            Gate::Rshift => a >> b,
-            _ => unreachable!("NOT already covered")
+            // shifting all the bits out of a 16-bit wire leaves it at 0
+            Gate::Lshift => a.checked_shl(*b as u32).unwrap_or(0),
+            Gate::Rshift => a.checked_shr(*b as u32).unwrap_or(0),
+            Gate::Not => return Err(Error::semantic("NOT takes a single input")),
         },
-        _ => unreachable!()
-    }
+        _ => return Err(Error::semantic(format!("malformed gate inputs: {inputs:?}"))),
+    };
+    Ok(signal)
 }
 
 
+/// `visiting` holds the wires whose signals are currently being worked out further up
+/// the stack, so a circuit that feeds back into itself is an error rather than a stack
+/// overflow.
 fn doit(
     wire: &str,
-    instructions: &HashMap<String, Vec<Input>>,
-    signals: &mut HashMap<String, u16>
+    instructions: &HashMap<&str, &[Input]>,
+    signals: &mut HashMap<String, u16>,
+    visiting: &mut HashSet<String>,
 )
-    -> Option<u16>
+    -> Result<u16>
 {
-    if signals.contains_key(wire) {
-        return signals.get(wire).cloned()
+    if let Some(sig) = signals.get(wire) {
+        return Ok(*sig)
+    }
+    if !visiting.insert(wire.to_owned()) {
+        return Err(Error::semantic(format!("wire {wire} depends on its own signal")))
     }
     //  123->x->NOT->h
     //        \
     //        and->d
     //        /
     //  456->y->NOT->i
+    let inputs = instructions
+        .get(wire)
+        .ok_or_else(|| {
+            Error::semantic(format!("nothing provides a signal to wire {wire}"))
+        })?;
     let mut new_inputs: Vec<Input> = vec![];
-    for input in instructions.get(wire)? {
+    for input in inputs.iter() {
         let new = match input {
             Input::Signal(s) => Input::Signal(*s),
             Input::Wire(w) => {
-                let sig = doit(&w, instructions, signals)?;
+                let sig = doit(w, instructions, signals, visiting)?;
                 signals.insert(w.to_owned(), sig);
                 Input::Signal(sig)
             },
@@ -196,63 +219,95 @@ fn doit(
         };
         new_inputs.push(new);
     }
-    Some(signal_from_processed_inputs(&new_inputs))
+    visiting.remove(wire);
+    signal_from_processed_inputs(&new_inputs)
 }
 
 
-fn part_1(input: String) -> Result<HashMap<String, u16>, String> {
-    let mut signals = HashMap::<String, u16>::new();
-    let instructions: HashMap<String, Vec<Input>> = try_parse_instructions(input)?
-        .into_iter()
-        .map(|ins| (ins.wire, ins.inputs))
+/// The signal on every wire of the circuit, starting from the wires already in
+/// `signals`, which keep theirs whatever the instructions say.
+fn run(
+    instructions: &[Instruction],
+    mut signals: HashMap<String, u16>,
+)
+    -> Result<HashMap<String, u16>>
+{
+    let instructions: HashMap<&str, &[Input]> = instructions
+        .iter()
+        .map(|ins| (ins.wire.as_str(), ins.inputs.as_slice()))
         .collect();
-    for (wire, _) in &instructions {
-        let sig = doit(&wire, &instructions, &mut signals).unwrap();
-        signals.insert(wire.clone(), sig);
+    for wire in instructions.keys() {
+        let sig = doit(wire, &instructions, &mut signals, &mut HashSet::new())?;
+        signals.insert(wire.to_string(), sig);
     }
     Ok(signals)
 }
 
 
-fn part_2(input: String) -> Result<HashMap<String, u16>, String> {
-    let mut signals = part_1(input.clone())?;
-    let a = signals.get("a").unwrap();
-    let mut instructions: HashMap<String, Vec<Input>> = try_parse_instructions(input)?
-        .into_iter()
-        .map(|ins| (ins.wire, ins.inputs))
-        .collect();
-    *instructions.get_mut("b").unwrap() = vec![Input::Signal(*a)];
-    signals.clear();
-    for (wire, _) in &instructions {
-        let sig = doit(&wire, &instructions, &mut signals).unwrap();
-        signals.insert(wire.to_owned(), sig);
+/// The signal on every wire of the circuit.
+pub fn part_1(instructions: &[Instruction]) -> Result<HashMap<String, u16>> {
+    run(instructions, HashMap::new())
+}
+
+
+/// The signal on every wire once wire `b` is overridden with part one's signal on `a`.
+pub fn part_2(instructions: &[Instruction]) -> Result<HashMap<String, u16>> {
+    if !instructions.iter().any(|ins| ins.wire == "b") {
+        return Err(Error::semantic("there is no wire b to override"))
+    }
+    let a = *part_1(instructions)?
+        .get("a")
+        .ok_or_else(|| Error::semantic("no signal on wire a"))?;
+    run(instructions, HashMap::from([(String::from("b"), a)]))
+}
+
+
+pub struct Day07;
+
+
+impl Solution for Day07 {
+    const DAY: u8 = 7;
+    const TITLE: &'static str = "Some Assembly Required";
+
+    type Input = Vec<Instruction>;
+    type Output1 = u16;
+    type Output2 = u16;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        try_parse_instructions(input.to_owned())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        let signals = part_1(input)?;
+        signals.get("a").copied().ok_or_else(|| Error::semantic("no signal on wire a"))
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        let signals = part_2(input)?;
+        signals.get("a").copied().ok_or_else(|| Error::semantic("no signal on wire a"))
     }
-    Ok(signals)
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::{path::Path, collections::HashMap};
+    use std::collections::HashMap;
 
-    fn load_input(fname: &str) -> String {
-        // TODO: add error handling and return Result
-        let path = Path::new(file!()).parent().unwrap().join(fname);
-        std::fs::read_to_string(path).unwrap()
-    }
+    use super::Day07;
+    use crate::{input::load_day, solution::Solution};
 
     #[test]
     fn part_1() {
-        let input = load_input("input.txt");
-        let output = super::part_1(input).unwrap();
+        let input = Day07::parse(&load_day(7).unwrap()).unwrap();
+        let output = super::part_1(&input).unwrap();
         let expected = 3176;
         assert_eq!(output.get("a").unwrap(), &expected);
     }
 
     #[test]
     fn part_1_sm() {
-        let input = load_input("input_sm.txt");
-        let output = super::part_1(input).unwrap();
+        let input = Day07::parse(include_str!("input_sm.txt")).unwrap();
+        let output = super::part_1(&input).unwrap();
         let expected = HashMap::<&str, u16>::from_iter(
             vec![
                 ("d", 72),
@@ -270,9 +325,24 @@ mod tests {
 
     #[test]
     fn part_2() {
-        let input = load_input("input.txt");
-        let output = super::part_2(input).unwrap();
+        let input = Day07::parse(&load_day(7).unwrap()).unwrap();
+        let output = super::part_2(&input).unwrap();
         let expected = 14710;
         assert_eq!(output.get("a").unwrap(), &expected);
     }
+
+    #[test]
+    fn malformed() {
+        let cases = vec![
+            ("123 -> x\nx AND -> y", "line 2, column 1: malformed gate expression: x AND"),
+            ("NOT 1 2 -> x", "line 1, column 1: malformed gate expression: NOT 1 2"),
+            ("1 -> x\nx -> ", "line 2, column 1: malformed input: x ->"),
+            ("1 -> b\na -> a", "wire a depends on its own signal"),
+            ("x AND y -> a", "nothing provides a signal to wire x"),
+        ];
+        for (input, expected) in cases {
+            let output = Day07::parse(input).and_then(|x| super::part_1(&x)).unwrap_err();
+            assert!(output.to_string().ends_with(expected), "{output}");
+        }
+    }
 }
@@ -53,22 +53,34 @@
 //! minus the characters in the original code representation (`23`, just like in the first
 //! part of this puzzle) is `42 - 23 = 19`.
 
-fn count_memory_chars(s: &str) -> usize {
-    let mut chars = s.trim().chars().peekable();
+use crate::{error::{Error, Result}, solution::Solution};
+
+
+fn count_memory_chars(s: &str) -> Result<usize> {
+    let mut chars = s.trim().chars().enumerate();
     let mut count: usize = 0;
-    while let Some(c) = chars.next() {
+    while let Some((i, c)) = chars.next() {
         if c == '\\' {
-            let nxt = chars.next().expect("a character did not follow an escape");
+            let (_, nxt) = chars.next().ok_or_else(|| {
+                Error::parse(1, i + 1, "a character did not follow an escape")
+            })?;
             if nxt == 'x' {
-                let _ = chars.next().expect("expected two hexadecimal chars");
-                let _ = chars.next().expect("expected two hexadecimal chars");
+                for _ in 0..2 {
+                    match chars.next() {
+                        Some((_, h)) if h.is_ascii_hexdigit() => {},
+                        _ => {
+                            let message = "expected two hexadecimal chars";
+                            return Err(Error::parse(1, i + 1, message))
+                        },
+                    }
+                }
             }
         } else if c == '"' {
             continue
         }
         count += 1;
     }
-    count
+    Ok(count)
 }
 
 
@@ -84,11 +96,11 @@ fn count_code_chars(s: &str) -> usize {
 /// This approach would is not only more consise, but it it also more robust: while the
 /// constraints of this problem only expect a couple escape char scenarios, this approach
 /// would also handle other escape chars (e.g. '\t') and patterns.
+#[cfg(test)]
 fn count_newstr_chars_std(s: &str) -> usize {
     let escaped = s.escape_default().to_string();
     let newstr = format!("\"{}\"", escaped);
-    let count = count_code_chars(&newstr);
-    count
+    count_code_chars(&newstr)
 }
 
 
@@ -103,24 +115,25 @@ fn count_newstr_chars_manual(s: &str) -> usize {
             _ => newstr.push(c),
         };
     }
-    newstr.push_str("\"");
-    let count = count_code_chars(&newstr);
-    count
+    newstr.push('"');
+    count_code_chars(&newstr)
 }
 
 
-fn part_1(input: &[String]) -> usize {
+/// Characters of code minus characters in memory, over every line.
+pub fn part_1(input: &[String]) -> Result<usize> {
     let mut memory_count: usize = 0;
     let mut code_count: usize = 0;
-    for s in input {
-        memory_count += count_memory_chars(s);
+    for (i, s) in input.iter().enumerate() {
+        memory_count += count_memory_chars(s).map_err(|e| e.on_line(i + 1))?;
         code_count += count_code_chars(s);
     }
-    code_count - memory_count
+    Ok(code_count - memory_count)
 }
 
 
-fn part_2(input: &[String]) -> usize {
+/// Characters of the re-encoded strings minus characters of code, over every line.
+pub fn part_2(input: &[String]) -> usize {
     let mut code_count: usize = 0;
     let mut encoded_count: usize = 0;
     for s in input {
@@ -131,46 +144,77 @@ fn part_2(input: &[String]) -> usize {
 }
 
 
+pub struct Day08;
+
+
+impl Solution for Day08 {
+    const DAY: u8 = 8;
+    const TITLE: &'static str = "Matchsticks";
+
+    type Input = Vec<String>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(String::from).collect())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        part_1(input)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        Ok(part_2(input))
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
-    use std::{path::Path, fs::File, io::{BufRead, BufReader}};
-
-    fn load_input(file_name: &str) -> std::io::Result<Vec<String>> {
-        let path = Path::new(file!())
-            .parent()
-            .expect("every file should have a parent");
-        let file = File::open(path.join(file_name))?;
-        let reader = BufReader::new(file);
-        let lines = reader
-            .lines()
-            .collect();
-        lines
-    }
+    use super::Day08;
+    use crate::{input::load_day, solution::Solution};
 
     #[test]
     fn part_1_sm() {
-        let input = load_input("input_sm.txt").unwrap();
-        let output = super::part_1(&input);
+        let input = Day08::parse(include_str!("input_sm.txt")).unwrap();
+        let output = super::part_1(&input).unwrap();
         assert_eq!(output, 12);
     }
 
     #[test]
     fn part_1() {
-        let input = load_input("input.txt").unwrap();
-        let output = super:: part_1(&input);
+        let input = Day08::parse(&load_day(8).unwrap()).unwrap();
+        let output = super:: part_1(&input).unwrap();
         assert_eq!(output, 1333);
     }
 
     #[test]
     fn part_2_sm() {
-        let input = load_input("input_sm.txt").unwrap();
+        let input = Day08::parse(include_str!("input_sm.txt")).unwrap();
         let output = super::part_2(&input);
         assert_eq!(output, 19);
     }
     #[test]
     fn part_2() {
-        let input = load_input("input.txt").unwrap();
+        let input = Day08::parse(&load_day(8).unwrap()).unwrap();
         let output = super::part_2(&input);
         assert_eq!(output, 2046);
     }
+
+    #[test]
+    fn newstr_chars() {
+        let input = Day08::parse(&load_day(8).unwrap()).unwrap();
+        for s in &input {
+            let expected = super::count_newstr_chars_manual(s);
+            assert_eq!(super::count_newstr_chars_std(s), expected, "{s}");
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        let input = vec![String::from(r#""abc""#), String::from(r#""a\x2""#)];
+        let e = super::part_1(&input).unwrap_err();
+        let expected = "parse error at line 2, column 3: expected two hexadecimal chars";
+        assert_eq!(e.to_string(), expected);
+    }
 }
@@ -8,14 +8,14 @@
 //! 
 //! For example, given the following distances:
 //! 
-//! ```
+//! ```text
 //! London to Dublin = 464
 //! London to Belfast = 518
 //! Dublin to Belfast = 141
 //! ```
 //! The possible routes are therefore:
 //! 
-//! ```
+//! ```text
 //! Dublin -> London -> Belfast = 982
 //! London -> Dublin -> Belfast = 605
 //! London -> Belfast -> Dublin = 659
@@ -42,18 +42,20 @@
 
 use std::collections::HashMap;
 
+use crate::{error::{Error, Result}, solution::Solution};
+
 #[derive(Debug)]
-struct Edge {
-    from: String,
-    to: String,
-    distance: usize,
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub distance: usize,
 }
 
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-struct Route {
-    stops: Vec<String>,
-    distance: usize,
+pub struct Route {
+    pub stops: Vec<String>,
+    pub distance: usize,
 }
 
 
@@ -65,26 +67,32 @@ impl Edge {
 }
 
 impl TryFrom<&String> for Edge {
-    type Error = String;
-    fn try_from(value: &String) -> Result<Self, Self::Error> {
+    type Error = Error;
+    fn try_from(value: &String) -> Result<Self> {
+        let value = value.trim_end();
         let splits: Vec<&str> = value.split(" = ").collect();
-        if splits.len() != 2 { return Err(String::from("invalid input for edge")) }
+        if splits.len() != 2 { return Err(Error::parse(1, 1, "invalid input for edge")) }
         let (lhs, rhs) = (splits[0], splits[1]);
         let lhs_splits: Vec<&str> = lhs.split(" to ").collect();
-        if lhs_splits.len() != 2 { return Err(format!("invalid lhs for edge: {}", lhs)) }
+        if lhs_splits.len() != 2 {
+            return Err(Error::parse(1, 1, format!("invalid lhs for edge: {}", lhs)))
+        }
         let (from, to) = (lhs_splits[0].to_owned(), lhs_splits[1].to_owned());
-        let distance: usize = rhs.parse().map_err(|e| String::from("invalid distance"))?;
+        let distance: usize = rhs
+            .parse()
+            .map_err(|e| Error::parse(1, lhs.len() + 4, format!("invalid distance: {e}")))?;
         Ok(Self { from, to, distance })
     }
 }
 
 
-fn edges_from_input(input: &[String]) -> Result<Vec<Edge>, String> {
-    let out: Result<Vec<Edge>, String> = input
-        .into_iter()
-        .map(|s| s.try_into())
-        .collect();
-    out
+pub fn edges_from_input(input: &[String]) -> Result<Vec<Edge>> {
+    input
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.trim().is_empty())
+        .map(|(i, s)| Edge::try_from(s).map_err(|e| e.on_line(i + 1)))
+        .collect()
 }
 
 
@@ -93,17 +101,19 @@ fn backtrack(
     route: &mut Route,
     graph: &HashMap<String, Vec<(String, usize)>>,
 )
-    -> Result<(), String>
+    -> Result<()>
 {
-    if route.stops.is_empty() {
-        return Err(String::from("backtrack should always receive a non-empty path"))
-    }
+    let Some(current) = route.stops.last() else {
+        return Err(Error::semantic("backtrack should always receive a non-empty path"))
+    };
     if route.stops.len() == graph.len() {
         paths.push(route.clone());
         return Ok(())
     }
-    let current = route.stops.last().unwrap();
-    for (v, dist) in graph.get(current).expect("all vertices should have a key in graph") {
+    let neighbours = graph
+        .get(current)
+        .ok_or_else(|| Error::semantic(format!("{current} is missing from the graph")))?;
+    for (v, dist) in neighbours {
         if route.stops.contains(v) { continue }
         route.stops.push(v.to_owned());
         route.distance += dist;
@@ -115,19 +125,20 @@ fn backtrack(
 }
 
 
-fn get_hamiltonian_paths(graph: &HashMap<String, Vec<(String, usize)>>)
-    -> Result<Vec<Route>, String>
+/// Every route that visits each location exactly once, in both directions.
+pub fn get_hamiltonian_paths(graph: &HashMap<String, Vec<(String, usize)>>)
+    -> Result<Vec<Route>>
 {
     let mut paths = vec![];
     for start in graph.keys() {
         let mut route = Route { stops: vec![start.to_owned()], distance: 0 };
         backtrack(&mut paths, &mut route, graph)?;
     }
-    return Ok(paths)
+    Ok(paths)
 }
 
 
-fn graph_from_edges(edges: &[Edge]) -> HashMap<String, Vec<(String, usize)>> {
+pub fn graph_from_edges(edges: &[Edge]) -> HashMap<String, Vec<(String, usize)>> {
     let mut out = HashMap::<String, Vec<(String, usize)>>::new();
     for e in edges {
         out.entry(e.from.clone())
@@ -141,10 +152,9 @@ fn graph_from_edges(edges: &[Edge]) -> HashMap<String, Vec<(String, usize)>> {
 }
 
 
-fn part_1(input: &[String]) -> Result<Option<usize>, String> {
-    let edges = edges_from_input(input)?;
-    let graph = graph_from_edges(&edges);
-    let paths = get_hamiltonian_paths(&graph)?;
+/// The length of the shortest route through every location.
+pub fn part_1(graph: &HashMap<String, Vec<(String, usize)>>) -> Result<Option<usize>> {
+    let paths = get_hamiltonian_paths(graph)?;
     let min_route = paths.iter().min_by(|a, b| a.distance.cmp(&b.distance));
     match min_route {
         Some(route) => Ok(Some(route.distance)),
@@ -153,10 +163,9 @@ fn part_1(input: &[String]) -> Result<Option<usize>, String> {
 }
 
 
-fn part_2(input: &[String]) -> Result<Option<usize>, String> {
-    let edges = edges_from_input(input)?;
-    let graph = graph_from_edges(&edges);
-    let paths = get_hamiltonian_paths(&graph)?;
+/// The length of the longest route through every location.
+pub fn part_2(graph: &HashMap<String, Vec<(String, usize)>>) -> Result<Option<usize>> {
+    let paths = get_hamiltonian_paths(graph)?;
     let max_route = paths.iter().max_by(|a, b| a.distance.cmp(&b.distance));
     match max_route {
         Some(route) => Ok(Some(route.distance)),
@@ -165,24 +174,42 @@ fn part_2(input: &[String]) -> Result<Option<usize>, String> {
 }
 
 
-#[cfg(test)]
-mod tests {
-    use std::{path::Path, fs::File, io::{BufReader, BufRead}};
+pub struct Day09;
+
+
+impl Solution for Day09 {
+    const DAY: u8 = 9;
+    const TITLE: &'static str = "All in a Single Night";
+
+    type Input = HashMap<String, Vec<(String, usize)>>;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let lines: Vec<String> = input.lines().map(String::from).collect();
+        Ok(graph_from_edges(&edges_from_input(&lines)?))
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        part_1(input)?.ok_or_else(|| Error::semantic("no route visits every location"))
+    }
 
-    fn load_input(fname: &str) -> std::io::Result<Vec<String>> {
-        let path = Path::new(file!())
-            .parent()
-            .expect("every file has a parent")
-            .join(fname);
-        let file = File::open(path)?;
-        let reader = BufReader::new(file);
-        reader.lines().collect()
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        part_2(input)?.ok_or_else(|| Error::semantic("no route visits every location"))
     }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Day09;
+    use crate::{input::load_day, solution::Solution};
 
     #[test]
     fn get_routes() {
         use super::Route;
-        let input = load_input("input_sm.txt").unwrap();
+        let input = include_str!("input_sm.txt");
+        let input: Vec<String> = input.lines().map(String::from).collect();
         let edges = super::edges_from_input(&input).unwrap();
         let graph = super::graph_from_edges(&edges);
         let mut output = super::get_hamiltonian_paths(&graph).unwrap();
@@ -222,7 +249,7 @@ mod tests {
 
     #[test]
     fn part_1_sm() {
-        let input = load_input("input_sm.txt").unwrap();
+        let input = Day09::parse(include_str!("input_sm.txt")).unwrap();
         let output = super::part_1(&input)
             .unwrap()
             .unwrap();
@@ -231,7 +258,7 @@ mod tests {
 
     #[test]
     fn part_1() {
-        let input = load_input("input.txt").unwrap();
+        let input = Day09::parse(&load_day(9).unwrap()).unwrap();
         let output = super::part_1(&input)
             .unwrap()
             .unwrap();
@@ -240,10 +267,20 @@ mod tests {
 
     #[test]
     fn part_2() {
-        let input = load_input("input.txt").unwrap();
+        let input = Day09::parse(&load_day(9).unwrap()).unwrap();
         let output = super::part_2(&input)
             .unwrap()
             .unwrap();
         assert_eq!(output, 736);
     }
+
+    #[test]
+    fn malformed() {
+        let input = "London to Dublin = 464\nLondon to Belfast = far";
+        let e = Day09::parse(input).unwrap_err();
+        let expected = "parse error at line 2, column 21: invalid distance";
+        assert!(e.to_string().starts_with(expected));
+        let input = Day09::parse("London to Dublin = 464\nBelfast to Paris = 1").unwrap();
+        assert!(Day09::part_one(&input).is_err());
+    }
 }
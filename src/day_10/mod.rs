@@ -27,8 +27,12 @@
 //! Now, starting again with the digits in your puzzle input, apply this process 50 times.
 //! What is the length of the new result?
 
+use std::{io::BufRead, iter::Peekable};
 
-fn next_sequence(input: &str) -> String {
+use crate::{error::{Error, Result}, solution::Solution, stream::Bytes};
+
+
+pub fn next_sequence(input: &str) -> String {
     let mut output = String::from("");
     let mut count: usize = 1;
     let mut chars = input.chars().peekable();
@@ -49,7 +53,8 @@ fn next_sequence(input: &str) -> String {
 }
 
 
-fn part_1(input: &str, n_iters: usize) -> usize {
+/// The length of the sequence after `n_iters` rounds of look-and-say.
+pub fn part_1(input: &str, n_iters: usize) -> usize {
     let mut output = String::from(input);
     for _ in 0..n_iters {
         output = next_sequence(&output);
@@ -58,6 +63,111 @@ fn part_1(input: &str, n_iters: usize) -> usize {
 }
 
 
+/// One round of look-and-say over a stream of ASCII digits, yielding the next
+/// sequence lazily. Rounds can be stacked to get any later sequence while only holding
+/// one run of digits per round in memory.
+pub struct LookAndSay<I: Iterator<Item = u8>> {
+    digits: Peekable<I>,
+    /// The rest of the current run's reading, last byte first.
+    pending: Vec<u8>,
+}
+
+
+impl<I: Iterator<Item = u8>> LookAndSay<I> {
+    pub fn new(digits: I) -> Self {
+        LookAndSay { digits: digits.peekable(), pending: Vec::new() }
+    }
+}
+
+
+impl<I: Iterator<Item = u8>> Iterator for LookAndSay<I> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        if let Some(byte) = self.pending.pop() {
+            return Some(byte)
+        }
+        let digit = self.digits.next()?;
+        let mut count: usize = 1;
+        while self.digits.next_if_eq(&digit).is_some() {
+            count += 1;
+        }
+        self.pending.push(digit);
+        loop {
+            self.pending.push(b'0' + (count % 10) as u8);
+            count /= 10;
+            if count == 0 {
+                break
+            }
+        }
+        self.pending.pop()
+    }
+}
+
+
+/// [`part_1`], streaming the starting digits from `reader` through `n_iters` stacked
+/// [`LookAndSay`] rounds. Trailing line endings are ignored.
+pub fn part_1_reader(reader: impl BufRead, n_iters: usize) -> Result<usize> {
+    let mut bytes = Bytes::new(reader);
+    let mut error = None;
+    let digits = std::iter::from_fn(|| loop {
+        let byte = match bytes.next()? {
+            Ok(byte) => byte,
+            Err(e) => {
+                error = Some(e);
+                return None
+            },
+        };
+        match byte {
+            b'0'..=b'9' => return Some(byte),
+            b'\n' | b'\r' => continue,
+            _ => {
+                error = Some(bytes.unexpected(byte));
+                return None
+            },
+        }
+    });
+    let mut sequence: Box<dyn Iterator<Item = u8> + '_> = Box::new(digits);
+    for _ in 0..n_iters {
+        sequence = Box::new(LookAndSay::new(sequence));
+    }
+    let len = sequence.count();
+    match error {
+        Some(e) => Err(e),
+        None => Ok(len),
+    }
+}
+
+
+pub struct Day10;
+
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+    const TITLE: &'static str = "Elves Look, Elves Say";
+
+    type Input = String;
+    type Output1 = usize;
+    type Output2 = usize;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input = input.trim();
+        if let Some((i, c)) = input.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+            return Err(Error::parse_at(input, i, format!("expected a digit, found {c:?}")))
+        }
+        Ok(input.to_owned())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        Ok(part_1(input, 40))
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        Ok(part_1(input, 50))
+    }
+}
+
+
 #[cfg(test)]
 mod tests {
 
@@ -82,4 +192,19 @@ mod tests {
         assert_eq!(output, 3579328);
     }
 
+    #[test]
+    fn look_and_say() {
+        let output: Vec<u8> = super::LookAndSay::new("111221".bytes()).collect();
+        assert_eq!(output, b"312211");
+        let output: Vec<u8> = super::LookAndSay::new("1111111111".bytes()).collect();
+        assert_eq!(output, b"101");
+    }
+
+    #[test]
+    fn reader() {
+        let output = super::part_1_reader("1113222113\n".as_bytes(), 40).unwrap();
+        assert_eq!(output, 252594);
+        assert!(super::part_1_reader("12a".as_bytes(), 1).is_err());
+    }
+
 }
@@ -36,28 +36,25 @@
 //!
 //! Given Santa's current password (your puzzle input), what should his next password be?
 
-use std::string::FromUtf8Error;
+use crate::{error::{Error, Result}, solution::Solution};
 
 fn contains_ascending(pwd: &str) -> bool {
     let mut chars = pwd.chars().peekable();
     let mut count: usize = 1;
     while let Some(c) = chars.next() {
         if count == 3 { return true }
-        match chars.peek() {
-            Some(nxt) => {
-                let x = c as u8;
-                let y = *nxt as u8;
-                if y <= x {
-                    count = 1;
-                    continue
-                }
-                if y - x == 1 {
-                    count += 1;
-                } else {
-                    count = 1;
-                }
+        if let Some(nxt) = chars.peek() {
+            let x = c as u8;
+            let y = *nxt as u8;
+            if y <= x {
+                count = 1;
+                continue
+            }
+            if y - x == 1 {
+                count += 1;
+            } else {
+                count = 1;
             }
-            None => {}
         }
     }
     count >= 3
@@ -65,7 +62,7 @@ fn contains_ascending(pwd: &str) -> bool {
 
 
 fn contains_iol(pwd: &str) -> bool {
-    pwd.find(&['i', 'o', 'l']).is_some()
+    pwd.find(['i', 'o', 'l']).is_some()
 }
 
 
@@ -74,43 +71,29 @@ fn contains_doubles(pwd: &str) -> bool {
     let mut count: usize = 0;
     while let Some(c) = chars.next() {
         if count == 2 { return true }
-        match chars.peek() {
-            Some(nxt) => {
-                if nxt == &c {
-                    let _ = chars.next();
-                    count += 1;
-                }
-            },
-            None => {}
+        if chars.peek() == Some(&c) {
+            let _ = chars.next();
+            count += 1;
         }
     }
     count >= 2
 }
 
 
-fn pwd_is_valid(pwd: &str) -> bool {
+pub fn pwd_is_valid(pwd: &str) -> bool {
     contains_ascending(pwd) && !contains_iol(pwd) && contains_doubles(pwd)
 }
 
 
-fn increment_char_idx(s: &str, idx: usize) -> Result<String, FromUtf8Error> {
-    let mut bytes = s.as_bytes().to_vec();
-    let new = match bytes[idx] {
-        x if x - 98 == 26 => 97,
-        x => x + 1
-    };
-    bytes[idx] = new;
-    String::from_utf8(bytes)
-}
-
-fn increment_char_idx_inplace(s: &mut str, idx: usize) -> Result<(), String> {
+fn increment_char_idx_inplace(s: &mut str, idx: usize) -> Result<()> {
+    // SAFETY: `s` has been validated as lowercase ASCII, and stays so.
     let bytes = unsafe { s.as_bytes_mut() };
     let new = match bytes[idx] {
         x if x - 96 == 26 => 97,
         x => x + 1
     };
-    if new < 97 || new > (97 + 25) {
-        return Err(String::from("new char ord would be out of bounds: {new}"))
+    if !(97..=(97 + 25)).contains(&new) {
+        return Err(Error::semantic(format!("new char ord would be out of bounds: {new}")))
     }
     bytes[idx] = new;
     Ok(())
@@ -118,14 +101,16 @@ fn increment_char_idx_inplace(s: &mut str, idx: usize) -> Result<(), String> {
 
 
 fn reset_tail_inplace(pwd: &mut str, idx: usize) {
+    // SAFETY: only ASCII is written.
     let bytes = unsafe { pwd.as_bytes_mut() };
-    for i in idx..bytes.len() {
-        bytes[i] = 97;
+    for b in bytes.iter_mut().skip(idx) {
+        *b = 97;
     }
 }
 
 
 fn increment_str_inplace(s: &mut str) {
+    // SAFETY: `s` has been validated as lowercase ASCII, and `z` wraps around to `a`.
     let bytes = unsafe { s.as_bytes_mut() };
     for x in bytes.iter_mut().rev() {
         match x {
@@ -139,32 +124,74 @@ fn increment_str_inplace(s: &mut str) {
 }
 
 
-fn increment_pwd(pwd: &mut str) -> Result<String, String> {
+/// Checks that `pwd` is a non-empty run of lowercase ASCII letters, which the
+/// incrementing relies on, as it works on the raw bytes.
+fn validate(pwd: &str) -> Result<()> {
+    if pwd.is_empty() {
+        return Err(Error::parse(1, 1, "the password is empty"))
+    }
+    if let Some((i, c)) = pwd.char_indices().find(|(_, c)| !c.is_ascii_lowercase()) {
+        return Err(Error::parse_at(pwd, i, format!("expected a-z, found {c:?}")))
+    }
+    Ok(())
+}
+
+
+/// Advances `pwd` in place to the next valid password.
+pub fn increment_pwd(pwd: &mut str) -> Result<String> {
+    validate(pwd)?;
     increment_str_inplace(pwd);
-    if let Some(i) = pwd.find(&['i', 'o', 'l']) {
+    if let Some(i) = pwd.find(['i', 'o', 'l']) {
         // increment char at idx
         increment_char_idx_inplace(pwd, i)?;
         // reset all chars after that to `a`
         reset_tail_inplace(pwd, i+1);
     }
     // TODO: optimize the next steps to be not entirely brute force
-    while !(contains_ascending(&pwd) && contains_doubles(&pwd)) {
+    while !(contains_ascending(pwd) && contains_doubles(pwd)) {
         increment_str_inplace(pwd);
     }
     Ok(pwd.to_owned())
 }
 
 
-fn part_1(input: &mut str) -> String {
-    increment_pwd(input).unwrap();
-    input.to_owned()
+pub fn part_1(input: &mut str) -> Result<String> {
+    increment_pwd(input)?;
+    Ok(input.to_owned())
 }
 
 
-fn part_2(input: &mut str) -> String {
-    increment_pwd(input).unwrap();
-    increment_pwd(input).unwrap();
-    input.to_owned()
+pub fn part_2(input: &mut str) -> Result<String> {
+    increment_pwd(input)?;
+    increment_pwd(input)?;
+    Ok(input.to_owned())
+}
+
+
+pub struct Day11;
+
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+    const TITLE: &'static str = "Corporate Policy";
+
+    type Input = String;
+    type Output1 = String;
+    type Output2 = String;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        let input = input.trim();
+        validate(input)?;
+        Ok(input.to_owned())
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        part_1(&mut input.clone())
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        part_2(&mut input.clone())
+    }
 }
 
 
@@ -175,7 +202,7 @@ mod tests {
     fn part_1() {
         let mut input = String::from("cqjxjnds");
         //let mut input = String::from("aqixjndz");
-        let output = super::part_1(&mut input);
+        let output = super::part_1(&mut input).unwrap();
         assert_eq!(output, "cqjxxyzz");
     }
 
@@ -183,8 +210,37 @@ mod tests {
     fn part_2() {
         let mut input = String::from("cqjxjnds");
         //let mut input = String::from("aqixjndz");
-        let output = super::part_2(&mut input);
+        let output = super::part_2(&mut input).unwrap();
         assert_eq!(output, "cqkaabcc");
     }
 
+    #[test]
+    fn validity() {
+        let cases = [
+            ("hijklmmn", false),
+            ("abbceffg", false),
+            ("abbcegjk", false),
+            ("abcdffaa", true),
+            ("ghjaabcc", true),
+        ];
+        for (pwd, expected) in cases {
+            assert_eq!(super::pwd_is_valid(pwd), expected, "{pwd}");
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        use crate::solution::Solution;
+
+        let e = super::Day11::parse("abcDefgh\n").unwrap_err();
+        let expected = "parse error at line 1, column 4: expected a-z, found 'D'";
+        assert_eq!(e.to_string(), expected);
+        assert!(super::Day11::parse("\n").is_err());
+        let mut input = String::from("\u{7f}");
+        assert!(super::increment_pwd(&mut input).is_err());
+        let mut input = String::from("abc!");
+        let e = super::part_1(&mut input).unwrap_err();
+        assert!(matches!(e, crate::error::Error::Parse { column: 4, .. }));
+        assert_eq!(input, "abc!");
+    }
 }
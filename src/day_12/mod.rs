@@ -30,135 +30,176 @@
 //!   is ignored.
 //! - `[1,"red",5]` has a sum of `6`, because `"red"` in an array has no effect.
 
-use std::num::ParseIntError;
-
 use serde_json::{self, Value, Number, Map};
 
+use crate::{error::{Error, Result}, solution::Solution};
+
+
+fn add(sum: i64, x: i64) -> Result<i64> {
+    sum.checked_add(x).ok_or_else(|| Error::semantic("the sum overflows an i64"))
+}
+
 
-fn update_sum(sum: &mut i64, digit: &mut String) -> Result<(), ParseIntError> {
+/// `end` is the offset in `input` just past the digits, used to locate parse errors.
+fn update_sum(sum: &mut i64, digit: &mut String, input: &str, end: usize) -> Result<()> {
     if !digit.is_empty() {
-        *sum += digit.parse::<i64>()?;
+        let x = digit.parse::<i64>().map_err(|e| {
+            Error::parse_at(input, end - digit.len(), format!("{e}: {digit}"))
+        })?;
+        *sum = add(*sum, x)?;
         digit.clear();
     }
     Ok(())
 }
 
 
-fn part_1(input: &str) -> Result<i64, ParseIntError> {
-    let mut chars = input.chars().peekable();
+/// The sum of every number in the document.
+pub fn part_1(input: &str) -> Result<i64> {
+    let mut chars = input.char_indices().peekable();
     let mut sum = 0;
     let mut digit = String::from("");
-    while let Some(c) = chars.next() {
+    while let Some((i, c)) = chars.next() {
         match c {
             '-' => if digit.is_empty() {
                 match chars.peek() {
-                    Some(nxt) => match nxt {
-                        '0'..='9' => digit.push(c),
-                        _ => {}
+                    Some((_, nxt)) => if nxt.is_ascii_digit() {
+                        digit.push(c)
                     },
-                    None => update_sum(&mut sum, &mut digit)?,
+                    None => update_sum(&mut sum, &mut digit, input, i)?,
                 }
             },
             '0'..='9' => digit.push(c),
-            _ => update_sum(&mut sum, &mut digit)?,
+            _ => update_sum(&mut sum, &mut digit, input, i)?,
         }
     }
+    update_sum(&mut sum, &mut digit, input, input.len())?;
     Ok(sum)
 }
 
 
-fn count_number(x: Number) -> Option<i64> {
-    x.as_i64()
+fn count_number(x: &Number) -> Result<i64> {
+    x.as_i64().ok_or_else(|| Error::semantic(format!("{x} is not an integer")))
 }
 
 
-fn count_vec(data: Vec<Value>) -> i64 {
+fn count_vec(data: &[Value], skip_red: bool) -> Result<i64> {
     let mut count: i64 = 0;
     for val in data {
         match val {
-            Value::Number(x) => count += count_number(x).expect("no!"),
-            Value::Array(v) => count += count_vec(v),
-            Value::Object(m) => count += count_map(m),
+            Value::Number(x) => count = add(count, count_number(x)?)?,
+            Value::Array(v) => count = add(count, count_vec(v, skip_red)?)?,
+            Value::Object(m) => count = add(count, count_map(m, skip_red)?)?,
             _ => {}
         }
     }
-    count
+    Ok(count)
 }
 
 
-fn count_map(data: Map<String, Value>) -> i64 {
+fn count_map(data: &Map<String, Value>, skip_red: bool) -> Result<i64> {
     let mut count: i64 = 0;
-    for (_, v) in data {
+    for v in data.values() {
         match v {
-            Value::Array(v) => count += count_vec(v),
-            Value::Number(x) => count += count_number(x).expect("no!"),
-            Value::String(s) => if s == "red" { return 0 } else { continue },
-            Value::Object(m) => count += count_map(m),
+            Value::Array(v) => count = add(count, count_vec(v, skip_red)?)?,
+            Value::Number(x) => count = add(count, count_number(x)?)?,
+            Value::String(s) if skip_red && s == "red" => return Ok(0),
+            Value::Object(m) => count = add(count, count_map(m, skip_red)?)?,
             _ => {}
         }
     }
-    count
+    Ok(count)
 }
 
 
-fn part_2(input: &str) -> Result<i64, String> {
-    let decoded = serde_json::from_str(input).map_err(|e| e.to_string())?;
-    let data = match decoded {
-        Value::Array(v) => v,
-        Value::Null => return Err(String::from("no data")),
-        _ => return Err(String::from("expected an array for input data"))
-    };
-    let mut count: i64 = 0;
-    for datum in data {
-        match datum {
-            Value::Array(v) => count += count_vec(v),
-            Value::Number(x) => count += count_number(x).expect("no!"),
-            Value::Object(m) => count += count_map(m),
-            _ => {},
-        }
+/// The sum of every number in the decoded document, ignoring objects with a `"red"`
+/// value if `skip_red`.
+pub fn sum_numbers(decoded: &Value, skip_red: bool) -> Result<i64> {
+    match decoded {
+        Value::Array(v) => count_vec(v, skip_red),
+        Value::Object(m) => count_map(m, skip_red),
+        Value::Number(x) => count_number(x),
+        Value::Null => Err(Error::semantic("no data")),
+        _ => Ok(0),
+    }
+}
+
+
+/// The sum of every number, ignoring objects with a `"red"` value.
+pub fn part_2(input: &str) -> Result<i64> {
+    sum_numbers(&serde_json::from_str(input)?, true)
+}
+
+
+pub struct Day12;
+
+
+impl Solution for Day12 {
+    const DAY: u8 = 12;
+    const TITLE: &'static str = "JSAbacusFramework.io";
+
+    type Input = Value;
+    type Output1 = i64;
+    type Output2 = i64;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(serde_json::from_str(input)?)
+    }
+
+    fn part_one(input: &Self::Input) -> Result<Self::Output1> {
+        sum_numbers(input, false)
+    }
+
+    fn part_two(input: &Self::Input) -> Result<Self::Output2> {
+        sum_numbers(input, true)
     }
-    Ok(count)
 }
 
 
 #[cfg(test)]
 mod tests {
-    use std::path::Path;
-
-    fn load_input(fname: &str) -> std::io::Result<String> {
-        let path = Path::new(file!())
-            .parent()
-            .expect("every file has a parent")
-            .join(fname);
-        std::fs::read_to_string(path)
-    }
+    use super::Day12;
+    use crate::{input::load_day, solution::Solution};
 
     #[test]
     fn part_1_sm() {
-        let input = load_input("input_sm.txt").unwrap();
-        let output = super::part_1(&input).unwrap();
+        let input = include_str!("input_sm.txt");
+        let output = super::part_1(input).unwrap();
         assert_eq!(output, 41);
     }
 
     #[test]
     fn part_1() {
-        let input = load_input("input.txt").unwrap();
+        let input = load_day(12).unwrap();
         let output = super::part_1(&input).unwrap();
         assert_eq!(output, 191164);
     }
 
     #[test]
     fn part_2_sm() {
-        let input = load_input("input_sm_red.txt").unwrap();
-        let output = super::part_2(&input).unwrap();
+        let input = include_str!("input_sm_red.txt");
+        let output = super::part_2(input).unwrap();
         assert_eq!(output, 35);
     }
 
     #[test]
     fn part_2() {
-        let input = load_input("input.txt").unwrap();
+        let input = load_day(12).unwrap();
         let output = super::part_2(&input).unwrap();
         assert_eq!(output, 87842);
+        let input = Day12::parse(&input).unwrap();
+        assert_eq!(Day12::part_one(&input).unwrap(), 191164);
+        assert_eq!(Day12::part_two(&input).unwrap(), 87842);
     }
 
+    #[test]
+    fn malformed() {
+        let e = super::part_2("[1, 2.5]").unwrap_err();
+        assert_eq!(e.to_string(), "2.5 is not an integer");
+        let e = super::part_2("[1,\n 2,]").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 2, column 4:"), "{e}");
+        let e = super::part_1("[1, 99999999999999999999]").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 1, column 5:"), "{e}");
+        let e = Day12::parse("{\"a\": [1,\n 2,]}").unwrap_err();
+        assert!(e.to_string().starts_with("parse error at line 2, column 4:"), "{e}");
+    }
 }
@@ -0,0 +1,132 @@
+//! The error type shared by every parser and solver.
+
+use std::fmt::Display;
+
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+
+#[derive(Debug)]
+pub enum Error {
+    /// The input is malformed. `line` and `column` are 1-based; single-line parsers
+    /// report line 1 and leave it to their callers to fix up via [`Error::on_line`].
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The input is well-formed but doesn't make sense for the puzzle, e.g. a circuit
+    /// that never drives wire `a`.
+    Semantic(String),
+    /// A worker thread panicked or lost its channel.
+    Worker(String),
+    Io(std::io::Error),
+}
+
+
+impl Error {
+    pub fn parse(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Error::Parse { line, column, message: message.into() }
+    }
+
+    /// A parse error at byte `offset` of `input`, with the line and column worked out
+    /// from the newlines before it.
+    pub fn parse_at(input: &str, offset: usize, message: impl Into<String>) -> Self {
+        let before = &input[..offset.min(input.len())];
+        let line = before.matches('\n').count() + 1;
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let column = before[line_start..].chars().count() + 1;
+        Error::parse(line, column, message)
+    }
+
+    pub fn semantic(message: impl Into<String>) -> Self {
+        Error::Semantic(message.into())
+    }
+
+    /// Moves a parse error produced for a single line onto line `line` of the whole
+    /// input; other errors pass through untouched.
+    pub fn on_line(self, line: usize) -> Self {
+        match self {
+            Error::Parse { column, message, .. } => Error::Parse { line, column, message },
+            other => other,
+        }
+    }
+}
+
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse { line, column, message } => {
+                write!(f, "parse error at line {line}, column {column}: {message}")
+            },
+            Error::Semantic(message) => write!(f, "{message}"),
+            Error::Worker(message) => write!(f, "worker failed: {message}"),
+            Error::Io(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Error::Io(value)
+    }
+}
+
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        match value.classify() {
+            serde_json::error::Category::Io => Error::Io(value.into()),
+            _ => Error::parse(value.line(), value.column(), value.to_string()),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+
+    fn position(e: Error) -> (usize, usize) {
+        match e {
+            Error::Parse { line, column, .. } => (line, column),
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_at() {
+        let input = "ab\ncde\n";
+        assert_eq!(position(Error::parse_at(input, 0, "")), (1, 1));
+        assert_eq!(position(Error::parse_at(input, 1, "")), (1, 2));
+        assert_eq!(position(Error::parse_at(input, 3, "")), (2, 1));
+        assert_eq!(position(Error::parse_at(input, 5, "")), (2, 3));
+    }
+
+    #[test]
+    fn on_line() {
+        let e = Error::parse(1, 4, "bad").on_line(7);
+        assert_eq!(position(e), (7, 4));
+        let e = Error::semantic("no route").on_line(7);
+        assert!(matches!(e, Error::Semantic(_)));
+    }
+
+    #[test]
+    fn display() {
+        let e = Error::parse(2, 3, "unexpected character 'x'");
+        let expected = "parse error at line 2, column 3: unexpected character 'x'";
+        assert_eq!(e.to_string(), expected);
+    }
+}
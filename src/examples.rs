@@ -0,0 +1,115 @@
+//! The worked examples from each puzzle's description, as a catalog the tests run.
+//!
+//! Each day keeps its examples beside its module in `examples.json`, a list of
+//! `{"part": 1, "input": "...", "expected": "..."}` objects, where `expected` is the
+//! answer as the [`Day`](crate::solution::Day) registry prints it. Day 10's examples
+//! are single look-and-say steps rather than whole answers, so it has no catalog.
+
+use serde_json::Value;
+
+use crate::{error::{Error, Result}, solution::Part};
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Example {
+    pub part: Part,
+    pub input: String,
+    pub expected: String,
+}
+
+
+impl TryFrom<&Value> for Example {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self> {
+        let field = |name: &str| {
+            value.get(name)
+                .ok_or_else(|| Error::semantic(format!("example lacks `{name}`")))
+        };
+        let string = |name: &str| {
+            field(name)?
+                .as_str()
+                .map(String::from)
+                .ok_or_else(|| Error::semantic(format!("example `{name}` is not a string")))
+        };
+        let part = match field("part")?.as_u64() {
+            Some(1) => Part::One,
+            Some(2) => Part::Two,
+            _ => return Err(Error::semantic("example `part` must be 1 or 2")),
+        };
+        Ok(Example { part, input: string("input")?, expected: string("expected")? })
+    }
+}
+
+
+pub fn parse(json: &str) -> Result<Vec<Example>> {
+    let value: Value = serde_json::from_str(json)?;
+    value.as_array()
+        .ok_or_else(|| Error::semantic("examples must be a list"))?
+        .iter()
+        .map(Example::try_from)
+        .collect()
+}
+
+
+/// The raw `examples.json` for `day`, if it has one.
+pub fn catalog(day: u8) -> Option<&'static str> {
+    macro_rules! catalog {
+        ($($day:literal => $dir:literal),* $(,)?) => {
+            match day {
+                $($day => Some(include_str!(concat!($dir, "/examples.json"))),)*
+                _ => None,
+            }
+        };
+    }
+    catalog!(
+        1 => "day_01",
+        2 => "day_02",
+        3 => "day_03",
+        4 => "day_04",
+        5 => "day_05",
+        6 => "day_06",
+        7 => "day_07",
+        8 => "day_08",
+        9 => "day_09",
+        11 => "day_11",
+        12 => "day_12",
+    )
+}
+
+
+pub fn load(day: u8) -> Result<Vec<Example>> {
+    catalog(day).map(parse).unwrap_or_else(|| Ok(Vec::new()))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Example;
+    use crate::solution::{self, Part};
+
+    #[test]
+    fn parse() {
+        let json = r#"[{"part": 2, "input": "()())", "expected": "5"}]"#;
+        let (input, expected) = (String::from("()())"), String::from("5"));
+        let expected = Example { part: Part::Two, input, expected };
+        assert_eq!(super::parse(json).unwrap(), vec![expected]);
+        assert!(super::parse(r#"[{"part": 3, "input": "", "expected": ""}]"#).is_err());
+        assert!(super::parse(r#"[{"part": 1, "input": "", "expected": 5}]"#).is_err());
+    }
+
+    #[test]
+    fn catalog() {
+        for day in solution::registry() {
+            let examples = super::load(day.day()).unwrap();
+            for example in examples {
+                let parsed = day.parse(&example.input).unwrap();
+                let output = day.solve(parsed.as_ref(), example.part).unwrap();
+                assert_eq!(
+                    output, example.expected,
+                    "day {} part {} on {:?}", day.day(), example.part, example.input,
+                );
+            }
+        }
+    }
+}
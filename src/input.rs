@@ -0,0 +1,278 @@
+//! Loading puzzle inputs.
+//!
+//! Inputs live at `inputs/dayNN.txt`. When no explicit source is given, the first of
+//! these that exists is used:
+//!
+//! 1. `dayNN.txt` in the directory named by the `AOC_INPUT_DIR` environment variable
+//! 2. `inputs/dayNN.txt` relative to the working directory
+//! 3. the copy compiled into the binary, when built with the `embed-inputs` feature
+//! 4. `inputs/dayNN.txt` relative to the crate root, for runs from within the checkout
+//!
+//! Other people's inputs live at `inputs/<user>/dayNN.txt`, looked up in the same
+//! directories minus the embedded copy. The user [`DEFAULT_USER`] stands for the
+//! inputs above.
+
+use std::{io::Read, path::{Path, PathBuf}};
+
+use crate::error::{Error, Result};
+
+
+pub const ENV_VAR: &str = "AOC_INPUT_DIR";
+pub const DIR: &str = "inputs";
+pub const DEFAULT_USER: &str = "default";
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    Path(PathBuf),
+    Stdin,
+    /// `dayNN.txt` in the directory named by [`ENV_VAR`].
+    Env,
+    /// `inputs/dayNN.txt`, relative to the working directory or else the crate root.
+    Convention,
+    /// The input compiled in with the `embed-inputs` feature.
+    Embedded,
+    /// `<user>/dayNN.txt` in any of the input directories.
+    User(String),
+}
+
+
+/// `-` means stdin, `@env`, `@inputs` and `@embedded` pick those sources and any other
+/// `@<user>` picks that user's input; anything else is a path.
+impl From<&str> for Source {
+    fn from(value: &str) -> Self {
+        match value {
+            "-" => Source::Stdin,
+            "@env" => Source::Env,
+            "@inputs" => Source::Convention,
+            "@embedded" => Source::Embedded,
+            user if user.starts_with('@') => Source::User(user[1..].to_owned()),
+            path => Source::Path(PathBuf::from(path)),
+        }
+    }
+}
+
+
+pub fn file_name(day: u8) -> String {
+    format!("day{day:02}.txt")
+}
+
+
+fn read_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| {
+        let message = format!("failed to read {}: {e}", path.display());
+        Error::Io(std::io::Error::new(e.kind(), message))
+    })
+}
+
+
+fn read_stdin() -> Result<String> {
+    let mut buf = String::new();
+    std::io::stdin().read_to_string(&mut buf)?;
+    Ok(buf)
+}
+
+
+fn env_path(day: u8) -> Option<PathBuf> {
+    let dir = std::env::var_os(ENV_VAR)?;
+    Some(PathBuf::from(dir).join(file_name(day)))
+}
+
+
+fn convention_paths(day: u8) -> [PathBuf; 2] {
+    [
+        Path::new(DIR).join(file_name(day)),
+        Path::new(env!("CARGO_MANIFEST_DIR")).join(DIR).join(file_name(day)),
+    ]
+}
+
+
+/// Every directory inputs are looked up in, most specific first.
+fn dirs() -> Vec<PathBuf> {
+    let env_dir = std::env::var_os(ENV_VAR).map(PathBuf::from);
+    env_dir.into_iter()
+        .chain([PathBuf::from(DIR), Path::new(env!("CARGO_MANIFEST_DIR")).join(DIR)])
+        .collect()
+}
+
+
+/// The path of `user`'s input for `day`, if there is one. An empty name is nobody's,
+/// rather than the input directory itself.
+pub fn user_path(day: u8, user: &str) -> Option<PathBuf> {
+    if user.is_empty() {
+        return None
+    }
+    dirs().into_iter()
+        .map(|dir| dir.join(user).join(file_name(day)))
+        .find(|path| path.is_file())
+}
+
+
+fn users_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new()
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+
+/// [`DEFAULT_USER`] followed by everyone with a directory of inputs, sorted by name.
+pub fn users() -> Vec<String> {
+    let mut users: Vec<String> = dirs().iter().flat_map(|dir| users_in(dir)).collect();
+    users.sort();
+    users.dedup();
+    users.retain(|user| user != DEFAULT_USER);
+    users.insert(0, DEFAULT_USER.to_owned());
+    users
+}
+
+
+#[cfg(feature = "embed-inputs")]
+fn embedded(day: u8) -> Option<&'static str> {
+    macro_rules! embed {
+        ($($day:literal => $file:literal),* $(,)?) => {
+            match day {
+                $($day => Some(include_str!(
+                    concat!(env!("CARGO_MANIFEST_DIR"), "/inputs/", $file)
+                )),)*
+                _ => None,
+            }
+        };
+    }
+    embed!(
+        1 => "day01.txt",
+        2 => "day02.txt",
+        3 => "day03.txt",
+        4 => "day04.txt",
+        5 => "day05.txt",
+        6 => "day06.txt",
+        7 => "day07.txt",
+        8 => "day08.txt",
+        9 => "day09.txt",
+        10 => "day10.txt",
+        11 => "day11.txt",
+        12 => "day12.txt",
+    )
+}
+
+
+#[cfg(not(feature = "embed-inputs"))]
+fn embedded(_day: u8) -> Option<&'static str> {
+    None
+}
+
+
+pub fn load(day: u8, source: &Source) -> Result<String> {
+    match source {
+        Source::Path(path) => read_file(path),
+        Source::Stdin => read_stdin(),
+        Source::Env => {
+            let path = env_path(day)
+                .ok_or_else(|| Error::semantic(format!("{ENV_VAR} is not set")))?;
+            read_file(&path)
+        },
+        Source::Convention => {
+            let [local, crate_root] = convention_paths(day);
+            match local.is_file() {
+                true => read_file(&local),
+                false => read_file(&crate_root),
+            }
+        },
+        Source::Embedded => embedded(day)
+            .map(String::from)
+            .ok_or_else(|| Error::semantic(format!(
+                "no embedded input for day {day} (build with `--features embed-inputs`)"
+            ))),
+        Source::User(user) => load_user(day, user),
+    }
+}
+
+
+/// Loads the input for `day` from the first source that has one; see the module docs
+/// for the order.
+pub fn load_day(day: u8) -> Result<String> {
+    if let Some(path) = env_path(day).filter(|p| p.is_file()) {
+        return read_file(&path)
+    }
+    let [local, crate_root] = convention_paths(day);
+    if local.is_file() {
+        return read_file(&local)
+    }
+    if let Some(input) = embedded(day) {
+        return Ok(input.to_owned())
+    }
+    read_file(&crate_root)
+}
+
+
+/// Loads `user`'s input for `day`; [`DEFAULT_USER`] goes through [`load_day`].
+pub fn load_user(day: u8, user: &str) -> Result<String> {
+    if user == DEFAULT_USER {
+        return load_day(day)
+    }
+    if user.is_empty() {
+        return Err(Error::semantic("the user name is empty"))
+    }
+    let path = user_path(day, user).ok_or_else(|| {
+        Error::semantic(format!("no input for day {day} from user {user}"))
+    })?;
+    read_file(&path)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::Source;
+
+    #[test]
+    fn file_name() {
+        assert_eq!(super::file_name(3), "day03.txt");
+        assert_eq!(super::file_name(12), "day12.txt");
+    }
+
+    #[test]
+    fn source_from_str() {
+        assert_eq!(Source::from("-"), Source::Stdin);
+        assert_eq!(Source::from("@env"), Source::Env);
+        assert_eq!(Source::from("@inputs"), Source::Convention);
+        assert_eq!(Source::from("a.txt"), Source::Path(PathBuf::from("a.txt")));
+        assert_eq!(Source::from("@alice"), Source::User(String::from("alice")));
+    }
+
+    #[test]
+    fn users_in() {
+        let name = format!("aoc-2015-rs-users-{}", std::process::id());
+        let dir = std::env::temp_dir().join(name);
+        std::fs::create_dir_all(dir.join("alice")).unwrap();
+        std::fs::write(dir.join("day01.txt"), "").unwrap();
+        let output = super::users_in(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(output, vec![String::from("alice")]);
+    }
+
+    #[test]
+    fn unknown_user() {
+        assert!(super::load_user(1, "nobody-has-this-name").is_err());
+        assert!(super::load(1, &Source::from("@")).is_err());
+        assert!(super::user_path(1, "").is_none());
+        assert_eq!(super::users()[0], super::DEFAULT_USER);
+    }
+
+    #[test]
+    fn convention() {
+        let input = super::load(4, &Source::Convention).unwrap();
+        assert_eq!(input.trim(), "bgvyzdsv");
+    }
+
+    #[test]
+    fn missing_path() {
+        let source = Source::Path(PathBuf::from("no/such/file.txt"));
+        assert!(super::load(1, &source).is_err());
+    }
+}
@@ -0,0 +1,36 @@
+//! Solutions to Advent of Code 2015.
+//!
+//! Each `day_NN` module exposes a unit struct implementing [`Solution`] along with the
+//! functions it is built from, so a single part can be run on any input without going
+//! through the registry:
+//!
+//! ```
+//! use aoc_2015_rs::{day_01::Day01, Solution};
+//!
+//! let floors = Day01::parse("(()(()(").unwrap();
+//! assert_eq!(Day01::part_one(&floors).unwrap(), 3);
+//! ```
+
+pub mod answers;
+pub mod bench;
+pub mod day_01;
+pub mod day_02;
+pub mod day_03;
+pub mod day_04;
+pub mod day_05;
+pub mod day_06;
+pub mod day_07;
+pub mod day_08;
+pub mod day_09;
+pub mod day_10;
+pub mod day_11;
+pub mod day_12;
+pub mod error;
+pub mod examples;
+pub mod input;
+pub mod solution;
+pub mod stream;
+pub mod tokens;
+
+pub use error::{Error, Result};
+pub use solution::{Day, Part, Solution};
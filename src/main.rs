@@ -1,16 +1,209 @@
-mod day_01;
-mod day_02;
-mod day_03;
-mod day_04;
-mod day_05;
-mod day_06;
-mod day_07;
-mod day_08;
-mod day_09;
-mod day_10;
-mod day_11;
-mod day_12;
-
-fn main() {
-    println!("Hello, world!");
+use std::process::ExitCode;
+
+use aoc_2015_rs::{
+    answers::{self, Outcome},
+    bench,
+    day_04,
+    input::{self, Source},
+    solution, Day, Error, Part, Result,
+};
+use cli::{Bench, Command, Users};
+
+mod cli;
+
+
+fn read_input(day: u8, source: Option<&Source>) -> Result<String> {
+    match source {
+        Some(source) => input::load(day, source),
+        None => input::load_day(day),
+    }
+}
+
+
+/// The users to run for; `Default` means everyone when `everyone_by_default` is set,
+/// else just [`input::DEFAULT_USER`].
+fn user_names(users: Users, everyone_by_default: bool) -> Vec<String> {
+    match users {
+        Users::Default if everyone_by_default => input::users(),
+        Users::Default => vec![input::DEFAULT_USER.to_owned()],
+        Users::One(user) => vec![user],
+        Users::All => input::users(),
+    }
+}
+
+
+/// Runs `day` once per user with an input for it, labelling each run by user unless
+/// there's only your own.
+fn run_users(day: &dyn Day, part: Option<Part>, users: &[String]) -> Result<()> {
+    let label = users.len() > 1 || users[0] != input::DEFAULT_USER;
+    for user in users {
+        if user != input::DEFAULT_USER && input::user_path(day.day(), user).is_none() {
+            continue
+        }
+        if label {
+            println!("user {user}:");
+        }
+        run(day, part, &input::load_user(day.day(), user)?)?;
+    }
+    Ok(())
+}
+
+
+/// Prints the bare answer when a single part is requested, so the output can be used
+/// in scripts; otherwise labels each part.
+fn run(day: &dyn Day, part: Option<Part>, input: &str) -> Result<()> {
+    let parsed = day.parse(input)?;
+    match part {
+        Some(part) => println!("{}", day.solve(parsed.as_ref(), part)?),
+        None => for part in Part::ALL {
+            println!("part {part}: {}", day.solve(parsed.as_ref(), part)?);
+        },
+    }
+    Ok(())
+}
+
+
+/// Times the day 4 miner on more and more threads, to check it keeps scaling.
+fn run_scaling(max_threads: usize, sample_ms: u64) -> Result<()> {
+    let input = read_input(4, None)?;
+    let sample = std::time::Duration::from_millis(sample_ms);
+    let batch = day_04::DEFAULT_BATCH;
+    let results = day_04::scaling(input.trim(), batch, max_threads, sample)?;
+    println!(
+        "{:>7}  {:>14}  {:>7}  {:>10}",
+        "threads", "hashes/s", "speedup", "efficiency",
+    );
+    for result in &results {
+        println!(
+            "{:>7}  {:>14.0}  {:>7.2}  {:>9.0}%",
+            result.n_threads, result.rate, result.speedup,
+            result.speedup / result.n_threads as f64 * 100.0,
+        );
+    }
+    println!("fewest threads within 5% of the best: {}", day_04::tuned(&results));
+    Ok(())
+}
+
+
+fn run_bench(args: Bench) -> Result<()> {
+    if let Some(max_threads) = args.scaling {
+        return run_scaling(max_threads, args.sample_ms)
+    }
+    let days = match args.day {
+        Some(day) => vec![solution::get(day)
+            .ok_or_else(|| Error::semantic(format!("day {day} is not implemented")))?],
+        None => solution::registry(),
+    };
+    let options = bench::Options { warmup: args.warmup, samples: args.samples };
+    let mut results = Vec::new();
+    for day in days {
+        let input = read_input(day.day(), None)?;
+        let result = bench::run_day(day.as_ref(), &input, options)?;
+        println!("Day {:>2}: {}", result.day, result.title);
+        for phase in bench::Phase::ALL {
+            let stats = result.phase(phase);
+            println!(
+                "  {phase:<7} median {:>12?}  p90 {:>12?}  p99 {:>12?}",
+                stats.median, stats.p90, stats.p99,
+            );
+        }
+        results.push(result);
+    }
+    if let Some(path) = &args.output {
+        let json = serde_json::to_string_pretty(&bench::to_json(&results))?;
+        std::fs::write(path, json + "\n")?;
+    }
+    if let Some(path) = &args.baseline {
+        let baseline = std::fs::read_to_string(path)?;
+        let baseline = bench::from_json(&serde_json::from_str(&baseline)?)?;
+        let regressions = bench::compare(&results, &baseline, args.threshold);
+        for regression in &regressions {
+            println!("regression: {regression}");
+        }
+        if !regressions.is_empty() {
+            return Err(Error::semantic(format!(
+                "{} phase(s) regressed by more than {}%", regressions.len(), args.threshold
+            )))
+        }
+    }
+    Ok(())
+}
+
+
+fn verify(users: Users) -> Result<()> {
+    let checks = answers::verify(&answers::load()?, &user_names(users, true));
+    println!(
+        "{:>3}  {:<4}  {:<10}  {:<6}  {:>12}",
+        "day", "part", "user", "result", "time",
+    );
+    for check in &checks {
+        let (result, detail) = match &check.outcome {
+            Outcome::Pass => ("pass", String::new()),
+            Outcome::Fail { actual } => ("FAIL", format!("  got {actual}")),
+            Outcome::Error(e) => ("ERROR", format!("  {e}")),
+            Outcome::Missing => ("-", String::from("  no answer recorded")),
+        };
+        println!(
+            "{:>3}  {:<4}  {:<10}  {:<6}  {:>12?}{detail}",
+            check.day, check.part, check.user, result, check.elapsed,
+        );
+    }
+    let failed = checks.iter()
+        .filter(|c| matches!(c.outcome, Outcome::Fail { .. } | Outcome::Error(_)))
+        .count();
+    match failed {
+        0 => Ok(()),
+        n => Err(Error::semantic(format!("{n} of {} checks failed", checks.len()))),
+    }
+}
+
+
+fn execute(command: Command) -> Result<()> {
+    match command {
+        Command::List => {
+            for day in solution::registry() {
+                println!("{:>2}  {}", day.day(), day.title());
+            }
+        },
+        Command::All(users) => {
+            let users = user_names(users, false);
+            for day in solution::registry() {
+                println!("Day {:>2}: {}", day.day(), day.title());
+                run_users(day.as_ref(), None, &users)?;
+            }
+        },
+        Command::Run { day, part, input, users } => {
+            let day = solution::get(day)
+                .ok_or_else(|| Error::semantic(format!("day {day} is not implemented")))?;
+            let input = match users {
+                Users::All => return run_users(day.as_ref(), part, &input::users()),
+                Users::One(user) => Some(Source::User(user)),
+                Users::Default => input,
+            };
+            let input = read_input(day.day(), input.as_ref())?;
+            run(day.as_ref(), part, &input)?;
+        },
+        Command::Verify(users) => verify(users)?,
+        Command::Bench(args) => run_bench(args)?,
+    }
+    Ok(())
+}
+
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match Command::try_from(args.as_slice()) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{}", cli::USAGE);
+            return ExitCode::FAILURE
+        },
+    };
+    match execute(command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        },
+    }
 }
@@ -0,0 +1,179 @@
+//! The shared interface every day implements, and a registry of all days by number.
+//!
+//! Each day module exposes a unit struct (e.g. `day_01::Day01`) implementing
+//! [`Solution`]. Since the parsed input and answer types differ from day to day, the
+//! registry stores days behind the type-erased [`Day`] trait, which only deals in
+//! strings, so tooling can look any day up and run it without per-day glue.
+
+use std::{any::Any, fmt::Display, marker::PhantomData, str::FromStr};
+
+use crate::{
+    error::{Error, Result},
+    day_01, day_02, day_03, day_04, day_05, day_06,
+    day_07, day_08, day_09, day_10, day_11, day_12,
+};
+
+
+/// One day's puzzle: parse the raw input once, then answer either part from it.
+pub trait Solution {
+    const DAY: u8;
+    const TITLE: &'static str;
+
+    type Input;
+    type Output1: Display;
+    type Output2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part_one(input: &Self::Input) -> Result<Self::Output1>;
+    fn part_two(input: &Self::Input) -> Result<Self::Output2>;
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Part {
+    One,
+    Two,
+}
+
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => f.pad("1"),
+            Part::Two => f.pad("2"),
+        }
+    }
+}
+
+
+impl FromStr for Part {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "1" | "one" => Ok(Part::One),
+            "2" | "two" => Ok(Part::Two),
+            _ => Err(format!("invalid part: {s}")),
+        }
+    }
+}
+
+
+/// Type-erased view of a [`Solution`], so days with different input and output types
+/// can live in the same registry.
+pub trait Day {
+    fn day(&self) -> u8;
+    fn title(&self) -> &'static str;
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>>;
+    fn solve(&self, parsed: &dyn Any, part: Part) -> Result<String>;
+}
+
+
+struct Entry<S>(PhantomData<S>);
+
+
+impl<S> Day for Entry<S>
+where
+    S: Solution,
+    S::Input: 'static,
+{
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn title(&self) -> &'static str {
+        S::TITLE
+    }
+
+    fn parse(&self, input: &str) -> Result<Box<dyn Any>> {
+        let parsed = S::parse(input)?;
+        Ok(Box::new(parsed))
+    }
+
+    fn solve(&self, parsed: &dyn Any, part: Part) -> Result<String> {
+        let input = parsed
+            .downcast_ref::<S::Input>()
+            .ok_or_else(|| {
+                Error::semantic(format!("parsed input does not belong to day {}", S::DAY))
+            })?;
+        match part {
+            Part::One => S::part_one(input).map(|x| x.to_string()),
+            Part::Two => S::part_two(input).map(|x| x.to_string()),
+        }
+    }
+}
+
+
+fn entry<S>() -> Box<dyn Day>
+where
+    S: Solution + 'static,
+    S::Input: 'static,
+{
+    Box::new(Entry::<S>(PhantomData))
+}
+
+
+/// Every implemented day, ordered by day number.
+pub fn registry() -> Vec<Box<dyn Day>> {
+    vec![
+        entry::<day_01::Day01>(),
+        entry::<day_02::Day02>(),
+        entry::<day_03::Day03>(),
+        entry::<day_04::Day04>(),
+        entry::<day_05::Day05>(),
+        entry::<day_06::Day06>(),
+        entry::<day_07::Day07>(),
+        entry::<day_08::Day08>(),
+        entry::<day_09::Day09>(),
+        entry::<day_10::Day10>(),
+        entry::<day_11::Day11>(),
+        entry::<day_12::Day12>(),
+    ]
+}
+
+
+pub fn get(day: u8) -> Option<Box<dyn Day>> {
+    registry().into_iter().find(|d| d.day() == day)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Part;
+
+    #[test]
+    fn registry_is_ordered() {
+        let days: Vec<u8> = super::registry().iter().map(|d| d.day()).collect();
+        assert_eq!(days, (1..=12).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn get() {
+        let day = super::get(8).unwrap();
+        assert_eq!(day.title(), "Matchsticks");
+        assert!(super::get(26).is_none());
+    }
+
+    #[test]
+    fn run_erased() {
+        let day = super::registry().remove(0);
+        let parsed = day.parse("(()(()(").unwrap();
+        assert_eq!(day.solve(parsed.as_ref(), Part::One).unwrap(), "3");
+        let parsed = day.parse("()())").unwrap();
+        assert_eq!(day.solve(parsed.as_ref(), Part::Two).unwrap(), "5");
+    }
+
+    #[test]
+    fn wrong_input_type() {
+        let mut days = super::registry();
+        let day_02 = days.remove(1);
+        let day_01 = days.remove(0);
+        let parsed = day_02.parse("2x3x4").unwrap();
+        assert!(day_01.solve(parsed.as_ref(), Part::One).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Reading input a byte at a time from any [`BufRead`], for the days whose input is a
+//! single stream of characters, so they run in constant memory on inputs of any size.
+
+use std::io::{BufRead, ErrorKind};
+
+use crate::error::{Error, Result};
+
+
+/// The bytes of a reader, keeping track of the line and column of the last one read
+/// so parse errors can point at it. Columns count bytes, not characters.
+pub struct Bytes<R> {
+    reader: R,
+    line: usize,
+    column: usize,
+    newline: bool,
+}
+
+
+impl<R: BufRead> Bytes<R> {
+    pub fn new(reader: R) -> Self {
+        Bytes { reader, line: 1, column: 0, newline: false }
+    }
+
+    /// A parse error at the last byte read.
+    pub fn error(&self, message: impl Into<String>) -> Error {
+        Error::parse(self.line, self.column, message)
+    }
+
+    /// A parse error for `byte`, the last byte read.
+    pub fn unexpected(&self, byte: u8) -> Error {
+        let message = match byte.is_ascii() {
+            true => format!("unexpected character {:?}", byte as char),
+            false => format!("unexpected byte 0x{byte:02x}"),
+        };
+        self.error(message)
+    }
+}
+
+
+impl<R: BufRead> Iterator for Bytes<R> {
+    type Item = Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let byte = loop {
+            match self.reader.fill_buf() {
+                Ok(buf) => break *buf.first()?,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Some(Err(e.into())),
+            }
+        };
+        self.reader.consume(1);
+        if self.newline {
+            self.line += 1;
+            self.column = 0;
+        }
+        self.column += 1;
+        self.newline = byte == b'\n';
+        Some(Ok(byte))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::Bytes;
+
+    #[test]
+    fn positions() {
+        let mut bytes = Bytes::new("ab\nc".as_bytes());
+        let mut errors = Vec::new();
+        while let Some(byte) = bytes.next() {
+            errors.push(bytes.unexpected(byte.unwrap()).to_string());
+        }
+        let expected = vec![
+            "parse error at line 1, column 1: unexpected character 'a'",
+            "parse error at line 1, column 2: unexpected character 'b'",
+            "parse error at line 1, column 3: unexpected character '\\n'",
+            "parse error at line 2, column 1: unexpected character 'c'",
+        ];
+        assert_eq!(errors, expected);
+    }
+}
@@ -0,0 +1,146 @@
+//! Configurable alphabets for the days whose input is a stream of moves.
+//!
+//! A [`TokenMap`] maps words of one or more characters to values (floor deltas for
+//! day 1, steps on the grid for day 3). Scanning matches the longest word at each point
+//! and skips line endings. Anything else is an error in [`Mode::Strict`] and skipped,
+//! but counted, in [`Mode::Lenient`].
+
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+
+
+/// How many times each unknown character was skipped.
+pub type Skipped = BTreeMap<char, usize>;
+
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Mode {
+    #[default]
+    Strict,
+    Lenient,
+}
+
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenMap<T> {
+    /// Longest word first, so scanning can take the first match.
+    words: Vec<(String, T)>,
+}
+
+
+impl<T> TokenMap<T> {
+    pub fn new<S: Into<String>>(words: impl IntoIterator<Item = (S, T)>) -> Result<Self> {
+        let mut words: Vec<(String, T)> = words.into_iter()
+            .map(|(word, value)| (word.into(), value))
+            .collect();
+        words.sort_by(|a, b| b.0.len().cmp(&a.0.len()).then_with(|| a.0.cmp(&b.0)));
+        if words.iter().any(|(word, _)| word.is_empty()) {
+            return Err(Error::semantic("tokens must not be empty"))
+        }
+        if let Some(w) = words.windows(2).find(|w| w[0].0 == w[1].0) {
+            return Err(Error::semantic(format!("token {:?} is mapped twice", w[0].0)))
+        }
+        Ok(TokenMap { words })
+    }
+
+    pub fn scan<'a>(&'a self, input: &'a str, mode: Mode) -> Scan<'a, T> {
+        let skipped = Skipped::new();
+        Scan { words: &self.words, input, mode, offset: 0, position: 0, skipped }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a, T> {
+    /// 1-based, counting every character before the token, skipped or not.
+    pub position: usize,
+    pub value: &'a T,
+}
+
+
+/// The tokens of an input, in order; see [`TokenMap::scan`].
+pub struct Scan<'a, T> {
+    words: &'a [(String, T)],
+    input: &'a str,
+    mode: Mode,
+    offset: usize,
+    position: usize,
+    skipped: Skipped,
+}
+
+
+impl<T> Scan<'_, T> {
+    /// The unknown characters skipped so far; always empty in strict mode.
+    pub fn skipped(&self) -> &Skipped {
+        &self.skipped
+    }
+
+    pub fn into_skipped(self) -> Skipped {
+        self.skipped
+    }
+}
+
+
+impl<'a, T> Iterator for Scan<'a, T> {
+    type Item = Result<Token<'a, T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let rest = &self.input[self.offset..];
+            let c = rest.chars().next()?;
+            let matched = self.words.iter().find(|(word, _)| rest.starts_with(word));
+            if let Some((word, value)) = matched {
+                let token = Token { position: self.position + 1, value };
+                self.offset += word.len();
+                self.position += word.chars().count();
+                return Some(Ok(token))
+            }
+            if !matches!(c, '\n' | '\r') {
+                if self.mode == Mode::Strict {
+                    let message = format!("unexpected character {c:?}");
+                    let e = Error::parse_at(self.input, self.offset, message);
+                    self.offset = self.input.len();
+                    return Some(Err(e))
+                }
+                *self.skipped.entry(c).or_default() += 1;
+            }
+            self.offset += c.len_utf8();
+            self.position += 1;
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::{Mode, TokenMap};
+
+    #[test]
+    fn longest_match() {
+        let tokens = TokenMap::new([("up", 1), ("u", 10), ("down", -1)]).unwrap();
+        let output: Vec<(usize, i32)> = tokens.scan("upu\r\ndown", Mode::Strict)
+            .map(|t| t.map(|t| (t.position, *t.value)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(output, vec![(1, 1), (3, 10), (6, -1)]);
+    }
+
+    #[test]
+    fn modes() {
+        let tokens = TokenMap::new([("(", 1), (")", -1)]).unwrap();
+        let e = tokens.scan("(\n(x)", Mode::Strict).find_map(|t| t.err()).unwrap();
+        let expected = "parse error at line 2, column 2: unexpected character 'x'";
+        assert_eq!(e.to_string(), expected);
+        let mut scan = tokens.scan("(x)é x", Mode::Lenient);
+        assert_eq!(scan.by_ref().filter(|t| t.is_ok()).count(), 2);
+        let skipped: Vec<(char, usize)> = scan.into_skipped().into_iter().collect();
+        assert_eq!(skipped, vec![(' ', 1), ('x', 2), ('é', 1)]);
+    }
+
+    #[test]
+    fn invalid_map() {
+        assert!(TokenMap::new([("", 1)]).is_err());
+        assert!(TokenMap::new([("a", 1), ("a", 2)]).is_err());
+    }
+}